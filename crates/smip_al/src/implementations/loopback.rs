@@ -0,0 +1,414 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, fmt, sync::Arc};
+
+use parking_lot::{Condvar, Mutex};
+use someip_types::*;
+
+use crate::*;
+
+/// In-process backend connecting the runtimes and clients created with it,
+/// without vsomeip, a routing manager or a network interface.
+///
+/// Services offered on a loopback are only reachable by clients of the same
+/// loopback, so independent tests can each use their own.
+///
+/// ```ignore
+/// let loopback = Loopback::new();
+///
+/// let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x1).backend(loopback.clone()))
+///     .service(MyService { x: 0 }, 30509)
+///     .start()?;
+///
+/// let client = Client::builder(&config).backend(loopback.clone()).build()?;
+/// ```
+#[derive(Clone, Default)]
+pub struct Loopback {
+    bus: Arc<Mutex<Bus>>,
+}
+
+impl fmt::Debug for Loopback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loopback").finish_non_exhaustive()
+    }
+}
+
+impl Loopback {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for Loopback {
+    /// Creates an application connected to this loopback, it's identified by its client id.
+    fn create_application(&self, _config: &ApplicationConfig) -> Result<Arc<dyn Application>, BackendError> {
+        let mut bus = self.bus.lock();
+
+        bus.next_client = bus.next_client.wrapping_add(1).max(1);
+        let id = bus.next_client;
+
+        let queue = Arc::new(Queue::default());
+        bus.applications.insert(id, queue.clone());
+
+        Ok(Arc::new(LoopbackApplication {
+            id,
+            bus: self.bus.clone(),
+            queue,
+            handlers: Mutex::new(Handlers::default()),
+            next_session: Mutex::new(0),
+        }))
+    }
+}
+
+/// Work for the dispatcher of an application, handled in order.
+enum Job {
+    Message(Message),
+    State(State),
+    Availability(ServiceId, InstanceId, bool),
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: VecDeque<Job>,
+    started: bool,
+    stopped: bool,
+}
+
+#[derive(Default)]
+struct Queue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
+
+impl Queue {
+    fn push(&self, job: Job) {
+        self.state.lock().jobs.push_back(job);
+        self.ready.notify_one();
+    }
+}
+
+struct Event {
+    eventgroups: Vec<EventGroupId>,
+    is_field: bool,
+    /// Last value of a field, sent to new subscribers.
+    value: Option<Payload>,
+}
+
+/// State shared by the applications of a [`Loopback`].
+#[derive(Default)]
+struct Bus {
+    next_client: ClientId,
+    applications: HashMap<ClientId, Arc<Queue>>,
+    /// Application offering each service instance.
+    offers: HashMap<(ServiceId, InstanceId), ClientId>,
+    events: HashMap<(ServiceId, InstanceId, EventId), Event>,
+    subscriptions: HashSet<(ClientId, ServiceId, InstanceId, EventGroupId)>,
+    /// Events applications requested as fields, only these get the current value on subscribing.
+    requested_fields: HashSet<(ClientId, ServiceId, InstanceId, EventId)>,
+    /// Applications waiting for the availability of a service instance.
+    watchers: HashSet<(ClientId, ServiceId, InstanceId)>,
+    next_notification: SessionId,
+}
+
+impl Bus {
+    fn send_to(&self, client: ClientId, job: Job) {
+        if let Some(queue) = self.applications.get(&client) {
+            queue.push(job);
+        }
+    }
+    fn set_available(&self, service_id: ServiceId, instance_id: InstanceId, available: bool) {
+        for (client, _, _) in self.watchers.iter().filter(|(_, service, instance)| *service == service_id && *instance == instance_id) {
+            self.send_to(*client, Job::Availability(service_id, instance_id, available));
+        }
+    }
+    fn notification(&mut self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, payload: &Payload) -> Message {
+        self.next_notification = self.next_notification.wrapping_add(1).max(1);
+
+        let mut message = Message::new(false);
+        message.set_service(service_id);
+        message.set_instance(instance_id);
+        message.set_method(event_id);
+        message.set_session(self.next_notification);
+        message.set_message_type(MessageType::Notification);
+        message.set_payload(payload);
+
+        message
+    }
+}
+
+/// Handlers are called without holding the lock of the map, so they can register other handlers.
+#[derive(Default)]
+struct Handlers {
+    state: Option<Arc<Mutex<StateHandler>>>,
+    messages: HashMap<(ServiceId, InstanceId, MethodId), Arc<Mutex<MessageHandler>>>,
+    availability: HashMap<(ServiceId, InstanceId), Arc<Mutex<AvailabilityHandler>>>,
+}
+
+struct LoopbackApplication {
+    id: ClientId,
+    bus: Arc<Mutex<Bus>>,
+    queue: Arc<Queue>,
+    handlers: Mutex<Handlers>,
+    next_session: Mutex<SessionId>,
+}
+
+impl LoopbackApplication {
+    fn dispatch(&self, job: Job) {
+        match job {
+            Job::Message(message) => {
+                let handler = {
+                    let handlers = self.handlers.lock();
+                    let key = (message.get_service(), message.get_instance());
+
+                    handlers.messages.get(&(key.0, key.1, message.get_method()))
+                        .or_else(|| handlers.messages.get(&(key.0, key.1, ANY_METHOD)))
+                        .cloned()
+                };
+
+                if let Some(handler) = handler {
+                    (handler.lock())(&message);
+                }
+            },
+            Job::State(state) => {
+                let handler = self.handlers.lock().state.clone();
+
+                if let Some(handler) = handler {
+                    (handler.lock())(state);
+                }
+            },
+            Job::Availability(service_id, instance_id, available) => {
+                let handler = self.handlers.lock().availability.get(&(service_id, instance_id)).cloned();
+
+                if let Some(handler) = handler {
+                    (handler.lock())(service_id, instance_id, available);
+                }
+            },
+        }
+    }
+}
+
+impl Application for LoopbackApplication {
+    fn start(&self) {
+        {
+            let mut state = self.queue.state.lock();
+            state.started = true;
+            state.jobs.push_back(Job::State(State::Registered));
+        }
+
+        loop {
+            let job = {
+                let mut state = self.queue.state.lock();
+                loop {
+                    if state.stopped {
+                        return;
+                    }
+                    if let Some(job) = state.jobs.pop_front() {
+                        break job;
+                    }
+                    self.queue.ready.wait(&mut state);
+                }
+            };
+
+            self.dispatch(job);
+        }
+    }
+    fn stop(&self) {
+        let mut bus = self.bus.lock();
+
+        let offers = bus.offers.iter()
+            .filter(|(_, client)| **client == self.id)
+            .map(|(service, _)| *service)
+            .collect::<Vec<_>>();
+        for (service_id, instance_id) in offers {
+            bus.offers.remove(&(service_id, instance_id));
+            bus.set_available(service_id, instance_id, false);
+        }
+
+        bus.subscriptions.retain(|(client, ..)| *client != self.id);
+        bus.watchers.retain(|(client, ..)| *client != self.id);
+        bus.applications.remove(&self.id);
+
+        self.queue.state.lock().stopped = true;
+        self.queue.ready.notify_all();
+    }
+    fn offer_service(&self, service_id: ServiceId, instance_id: InstanceId, _major_version: MajorVersion, _minor_version: MinorVersion) {
+        let mut bus = self.bus.lock();
+
+        if bus.offers.insert((service_id, instance_id), self.id).is_none() {
+            bus.set_available(service_id, instance_id, true);
+        }
+    }
+    fn stop_offer_service(&self, service_id: ServiceId, instance_id: InstanceId, _major_version: MajorVersion, _minor_version: MinorVersion) {
+        let mut bus = self.bus.lock();
+
+        if bus.offers.get(&(service_id, instance_id)) == Some(&self.id) {
+            bus.offers.remove(&(service_id, instance_id));
+            bus.set_available(service_id, instance_id, false);
+        }
+    }
+    fn offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType) {
+        self.bus.lock().events.entry((service_id, instance_id, event_id)).or_insert_with(|| Event {
+            eventgroups: eventgroups.to_vec(),
+            is_field: event_type == EventType::Field,
+            value: None,
+        });
+    }
+    fn stop_offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId) {
+        self.bus.lock().events.remove(&(service_id, instance_id, event_id));
+    }
+    fn notify(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, payload: &Payload, force: bool) {
+        let mut bus = self.bus.lock();
+
+        let Some(event) = bus.events.get_mut(&(service_id, instance_id, event_id)) else {
+            return;
+        };
+
+        // Like vsomeip, fields only notify when their value changed
+        if event.is_field {
+            if !force && event.value.as_ref() == Some(payload) {
+                return;
+            }
+            event.value = Some(payload.clone());
+        }
+
+        let eventgroups = event.eventgroups.clone();
+        let subscribers = bus.subscriptions.iter()
+            .filter(|(_, service, instance, eventgroup)| *service == service_id && *instance == instance_id && eventgroups.contains(eventgroup))
+            .map(|(client, ..)| *client)
+            .collect::<HashSet<_>>();
+
+        for client in subscribers {
+            let message = bus.notification(service_id, instance_id, event_id, payload);
+            bus.send_to(client, Job::Message(message));
+        }
+    }
+    fn request_service(&self, _service_id: ServiceId, _instance_id: InstanceId, _major_version: MajorVersion, _minor_version: MinorVersion) {
+        // Every service on the loopback can be reached without requesting it
+    }
+    fn release_service(&self, service_id: ServiceId, instance_id: InstanceId) {
+        self.bus.lock().watchers.remove(&(self.id, service_id, instance_id));
+    }
+    fn request_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, _eventgroups: &[EventGroupId], event_type: EventType) {
+        // Notifications are routed by the eventgroups the offering application registered
+        if event_type == EventType::Field {
+            self.bus.lock().requested_fields.insert((self.id, service_id, instance_id, event_id));
+        }
+    }
+    fn release_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId) {
+        self.bus.lock().requested_fields.remove(&(self.id, service_id, instance_id, event_id));
+    }
+    fn subscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, _major_version: MajorVersion, _event_id: EventId) {
+        let mut bus = self.bus.lock();
+
+        if !bus.subscriptions.insert((self.id, service_id, instance_id, eventgroup_id)) {
+            return;
+        }
+
+        // New subscribers receive the current value of the fields in the eventgroup they requested as fields
+        let values = bus.events.iter()
+            .filter(|((service, instance, _), event)| *service == service_id && *instance == instance_id && event.eventgroups.contains(&eventgroup_id))
+            .filter(|((service, instance, event_id), _)| bus.requested_fields.contains(&(self.id, *service, *instance, *event_id)))
+            .filter_map(|((_, _, event_id), event)| Some((*event_id, event.value.clone()?)))
+            .collect::<Vec<_>>();
+
+        for (event_id, value) in values {
+            let message = bus.notification(service_id, instance_id, event_id, &value);
+            bus.send_to(self.id, Job::Message(message));
+        }
+    }
+    fn unsubscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, _event_id: EventId) {
+        self.bus.lock().subscriptions.remove(&(self.id, service_id, instance_id, eventgroup_id));
+    }
+    fn send(&self, message: &mut Message) {
+        let bus = self.bus.lock();
+
+        match message.get_message_type() {
+            MessageType::Request | MessageType::RequestNoReturn => {
+                let mut session = self.next_session.lock();
+                *session = session.wrapping_add(1).max(1);
+
+                message.set_client(self.id);
+                message.set_session(*session);
+
+                // Like vsomeip, requests to unavailable services are dropped
+                if let Some(client) = bus.offers.get(&(message.get_service(), message.get_instance())) {
+                    bus.send_to(*client, Job::Message(message.clone()));
+                }
+            },
+            MessageType::Response | MessageType::Error => {
+                bus.send_to(message.get_client(), Job::Message(message.clone()));
+            },
+            _ => {},
+        }
+    }
+    fn register_state_handler(&self, handler: StateHandler) {
+        self.handlers.lock().state = Some(Arc::new(Mutex::new(handler)));
+
+        // The registration already happened if the application is running
+        let mut state = self.queue.state.lock();
+        if state.started {
+            state.jobs.push_back(Job::State(State::Registered));
+            self.queue.ready.notify_one();
+        }
+    }
+    fn register_message_handler(&self, service_id: ServiceId, instance_id: InstanceId, method_id: MethodId, handler: MessageHandler) {
+        self.handlers.lock().messages.insert((service_id, instance_id, method_id), Arc::new(Mutex::new(handler)));
+    }
+    fn unregister_message_handler(&self, service_id: ServiceId, instance_id: InstanceId, method_id: MethodId) {
+        self.handlers.lock().messages.remove(&(service_id, instance_id, method_id));
+    }
+    fn register_availability_handler(&self, service_id: ServiceId, instance_id: InstanceId, handler: AvailabilityHandler, _major_version: MajorVersion, _minor_version: MinorVersion) {
+        self.handlers.lock().availability.insert((service_id, instance_id), Arc::new(Mutex::new(handler)));
+
+        let mut bus = self.bus.lock();
+        bus.watchers.insert((self.id, service_id, instance_id));
+
+        if bus.offers.contains_key(&(service_id, instance_id)) {
+            self.queue.push(Job::Availability(service_id, instance_id, true));
+        }
+    }
+    fn clear_all_handlers(&self) {
+        *self.handlers.lock() = Handlers::default();
+        self.bus.lock().watchers.retain(|(client, ..)| *client != self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn test_field_sent_to_new_subscribers() {
+        let loopback = Loopback::new();
+        let config = ApplicationConfig::default();
+
+        let server = loopback.create_application(&config).unwrap();
+        let client = loopback.create_application(&config).unwrap();
+
+        server.offer_service(0x1234, 0x0001, 1, 0);
+        server.offer_event(0x1234, 0x0001, 0x8001, &[0x0001], EventType::Field);
+        server.notify(0x1234, 0x0001, 0x8001, &Payload::with_data(&[1]), false);
+
+        let (sender, receiver) = mpsc::channel();
+        client.register_message_handler(0x1234, 0x0001, ANY_METHOD, Box::new(move |message| {
+            let _ = sender.send(message.get_payload().get_data().to_vec());
+        }));
+        client.request_event(0x1234, 0x0001, 0x8001, &[0x0001], EventType::Field);
+        client.subscribe(0x1234, 0x0001, 0x0001, 1, 0x8001);
+
+        let client_thread = {
+            let client = client.clone();
+            thread::spawn(move || client.start())
+        };
+
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(1)).unwrap(), [1]);
+
+        // Unchanged values of fields aren't sent again
+        server.notify(0x1234, 0x0001, 0x8001, &Payload::with_data(&[1]), false);
+        server.notify(0x1234, 0x0001, 0x8001, &Payload::with_data(&[2]), false);
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(1)).unwrap(), [2]);
+
+        client.stop();
+        client_thread.join().unwrap();
+    }
+}
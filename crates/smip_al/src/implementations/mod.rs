@@ -1,2 +1,9 @@
+mod loopback;
 mod noop;
-mod vsomeip;
\ No newline at end of file
+#[cfg(feature = "vsomeip")]
+mod vsomeip;
+
+pub use loopback::Loopback;
+pub use noop::Noop;
+#[cfg(feature = "vsomeip")]
+pub use vsomeip::VsomeIp;
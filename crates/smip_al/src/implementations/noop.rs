@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use parking_lot::{Condvar, Mutex};
+use someip_types::*;
+
+use crate::*;
+
+/// Backend that doesn't connect to anything, for running services whose
+/// methods are never called over SOME/IP, e.g. in benchmarks.
+///
+/// Its applications register right away so services are offered and started,
+/// but messages and notifications are dropped and no service ever becomes available.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Noop;
+
+impl Backend for Noop {
+    fn create_application(&self, _config: &ApplicationConfig) -> Result<Arc<dyn Application>, BackendError> {
+        Ok(Arc::new(NoopApplication::default()))
+    }
+}
+
+#[derive(Default)]
+struct NoopApplication {
+    state_handler: Mutex<Option<StateHandler>>,
+    stopped: Mutex<bool>,
+    stop: Condvar,
+}
+
+impl Application for NoopApplication {
+    fn start(&self) {
+        if let Some(handler) = self.state_handler.lock().as_mut() {
+            handler(State::Registered);
+        }
+
+        let mut stopped = self.stopped.lock();
+        while !*stopped {
+            self.stop.wait(&mut stopped);
+        }
+    }
+    fn stop(&self) {
+        *self.stopped.lock() = true;
+        self.stop.notify_all();
+    }
+    fn offer_service(&self, _service_id: ServiceId, _instance_id: InstanceId, _major_version: MajorVersion, _minor_version: MinorVersion) {}
+    fn stop_offer_service(&self, _service_id: ServiceId, _instance_id: InstanceId, _major_version: MajorVersion, _minor_version: MinorVersion) {}
+    fn offer_event(&self, _service_id: ServiceId, _instance_id: InstanceId, _event_id: EventId, _eventgroups: &[EventGroupId], _event_type: EventType) {}
+    fn stop_offer_event(&self, _service_id: ServiceId, _instance_id: InstanceId, _event_id: EventId) {}
+    fn notify(&self, _service_id: ServiceId, _instance_id: InstanceId, _event_id: EventId, _payload: &Payload, _force: bool) {}
+    fn request_service(&self, _service_id: ServiceId, _instance_id: InstanceId, _major_version: MajorVersion, _minor_version: MinorVersion) {}
+    fn release_service(&self, _service_id: ServiceId, _instance_id: InstanceId) {}
+    fn request_event(&self, _service_id: ServiceId, _instance_id: InstanceId, _event_id: EventId, _eventgroups: &[EventGroupId], _event_type: EventType) {}
+    fn release_event(&self, _service_id: ServiceId, _instance_id: InstanceId, _event_id: EventId) {}
+    fn subscribe(&self, _service_id: ServiceId, _instance_id: InstanceId, _eventgroup_id: EventGroupId, _major_version: MajorVersion, _event_id: EventId) {}
+    fn unsubscribe(&self, _service_id: ServiceId, _instance_id: InstanceId, _eventgroup_id: EventGroupId, _event_id: EventId) {}
+    fn send(&self, _message: &mut Message) {}
+    fn register_state_handler(&self, handler: StateHandler) {
+        *self.state_handler.lock() = Some(handler);
+    }
+    fn register_message_handler(&self, _service_id: ServiceId, _instance_id: InstanceId, _method_id: MethodId, _handler: MessageHandler) {}
+    fn unregister_message_handler(&self, _service_id: ServiceId, _instance_id: InstanceId, _method_id: MethodId) {}
+    fn register_availability_handler(&self, _service_id: ServiceId, _instance_id: InstanceId, _handler: AvailabilityHandler, _major_version: MajorVersion, _minor_version: MinorVersion) {}
+    fn clear_all_handlers(&self) {
+        *self.state_handler.lock() = None;
+    }
+}
@@ -1,76 +1,261 @@
-use std::collections::HashMap;
+use std::sync::Arc;
+
+use someip_types::*;
+use vsomeip_compat::{set_vsomeip_config, ConfigFile, VSomeIpServiceConfig, VsomeIpConfig};
 
 use crate::*;
-pub struct VSomeIPService {
-    config: ServiceConfig,
-    methods: HashMap<MethodId, Box<dyn FnMut(vsomeip_rs::Message) + 'static>>
+
+/// The backend of vsomeip.
+///
+/// The configuration of each application is generated from the [`VsomeIpConfig`]
+/// of the backend, with the name and services of the [`ApplicationConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct VsomeIp {
+    config: VsomeIpConfig,
 }
 
-impl Service for VSomeIPService {
-    type B = vsomeip_rs::Application;
-    
-    fn with_method<F>(&mut self, method_id: MethodId, handler: F) where F: FnMut(<Self::B as Backend>::M) + 'static {
-        self.methods.insert(method_id, Box::new(handler));
+impl VsomeIp {
+    /// `config` holds the vsomeip settings, like addresses, service discovery and logging, of all
+    /// applications created by this backend.
+    pub fn new(config: VsomeIpConfig) -> Self {
+        Self {
+            config,
+        }
     }
 }
 
-impl Backend for vsomeip_rs::Application {
-    type S = VSomeIPService;
-    type M = vsomeip_rs::Message;
-    type P = vsomeip_rs::Payload;
-    
-    fn init(name: impl AsRef<str>) -> Self {
-        vsomeip_rs::Runtime::get().create_application_with_name(name)
-    }
-    fn create_service(config: &ServiceConfig) -> Self::S {
-        VSomeIPService {
-            config: config.clone(),
-            methods: HashMap::new()
+/// The application and services of a vsomeip configuration, services without an
+/// instance id get the one of the configuration.
+impl From<&VsomeIpConfig> for ApplicationConfig {
+    fn from(config: &VsomeIpConfig) -> Self {
+        Self {
+            name: config.app_id.0.clone(),
+            id: config.app_id.1,
+            services: config.services.iter().map(|service| ServiceConfig {
+                id: service.id,
+                instance_id: service.instance_id.unwrap_or(config.instance_id),
+                major_version: service.major_version,
+                minor_version: service.minor_version,
+                conn_type: service.conn_type,
+            }).collect(),
         }
     }
-    fn offer_service(&self, service: &Self::S) {
-        self.offer_service(service.config.id, service.config.instance_id, service.config.major_version, service.config.minor_version);
-        
-        for (method_id, handler) in service.methods {
-            self.register_message_handler(service_id, instance_id, method_id, f)
-        }
+}
+
+impl Backend for VsomeIp {
+    fn create_application(&self, config: &ApplicationConfig) -> Result<Arc<dyn Application>, BackendError> {
+        let mut vsomeip_config = self.config.clone();
+        vsomeip_config.app_id = (config.name.clone(), config.id);
+        vsomeip_config.services = config.services.iter().map(|service| VSomeIpServiceConfig {
+            id: service.id,
+            instance_id: Some(service.instance_id),
+            conn_type: service.conn_type,
+            major_version: service.major_version,
+            minor_version: service.minor_version,
+        }).collect();
+
+        let mut config_file = None;
+        let application = vsomeip_rs::Runtime::get().create_application_with(&config.name, |_app| {
+            config_file = set_vsomeip_config(&vsomeip_config);
+        }).map_err(|err| BackendError {
+            name: config.name.clone(),
+            source: Box::new(err),
+        })?;
+
+        Ok(Arc::new(VsomeIpApplication {
+            application,
+            _config_file: config_file,
+        }))
     }
-    fn create_payload(data: &[u8]) -> Self::P {
-        vsomeip_rs::Payload::with_data(data)
+}
+
+struct VsomeIpApplication {
+    application: vsomeip_rs::Application,
+    /// Generated configuration of the application, removed once it's dropped.
+    _config_file: Option<ConfigFile>,
+}
+
+fn return_code_from_vsomeip(value: vsomeip_rs::ReturnCode) -> ReturnCode {
+    match value {
+        vsomeip_rs::ReturnCode::Ok => ReturnCode::Ok,
+        vsomeip_rs::ReturnCode::NotOk => ReturnCode::NotOk,
+        vsomeip_rs::ReturnCode::UnknownService => ReturnCode::UnknownService,
+        vsomeip_rs::ReturnCode::UnknownMethod => ReturnCode::UnknownMethod,
+        vsomeip_rs::ReturnCode::NotReady => ReturnCode::NotReady,
+        vsomeip_rs::ReturnCode::NotReachable => ReturnCode::NotReachable,
+        vsomeip_rs::ReturnCode::Timeout => ReturnCode::Timeout,
+        vsomeip_rs::ReturnCode::WrongProtocolVersion => ReturnCode::WrongProtocolVersion,
+        vsomeip_rs::ReturnCode::WrongInterfaceVersion => ReturnCode::WrongInterfaceVersion,
+        vsomeip_rs::ReturnCode::MalformedMessage => ReturnCode::MalformedMessage,
+        vsomeip_rs::ReturnCode::WrongMessageType => ReturnCode::WrongMessageType,
+        vsomeip_rs::ReturnCode::Application(code) => ReturnCode::Application(code),
+        vsomeip_rs::ReturnCode::Unknown => ReturnCode::Unknown,
     }
-    fn create_message(header: &SOMEIpHeader, reliable: bool, payload: Self::P) -> Self::M {
-        let mut message = vsomeip_rs::Message::new(reliable);
+}
 
-        message.set_payload(&payload);
-        message.set_service(header.service_id);
-        message.set_method(header.method_id);
-        message.set_return_code(header.return_code.into());
-        // message.set_message_type();
-        // message.set_client();
-        // message
+fn return_code_to_vsomeip(value: ReturnCode) -> vsomeip_rs::ReturnCode {
+    match value {
+        ReturnCode::Ok => vsomeip_rs::ReturnCode::Ok,
+        ReturnCode::NotOk => vsomeip_rs::ReturnCode::NotOk,
+        ReturnCode::UnknownService => vsomeip_rs::ReturnCode::UnknownService,
+        ReturnCode::UnknownMethod => vsomeip_rs::ReturnCode::UnknownMethod,
+        ReturnCode::NotReady => vsomeip_rs::ReturnCode::NotReady,
+        ReturnCode::NotReachable => vsomeip_rs::ReturnCode::NotReachable,
+        ReturnCode::Timeout => vsomeip_rs::ReturnCode::Timeout,
+        ReturnCode::WrongProtocolVersion => vsomeip_rs::ReturnCode::WrongProtocolVersion,
+        ReturnCode::WrongInterfaceVersion => vsomeip_rs::ReturnCode::WrongInterfaceVersion,
+        ReturnCode::MalformedMessage => vsomeip_rs::ReturnCode::MalformedMessage,
+        ReturnCode::WrongMessageType => vsomeip_rs::ReturnCode::WrongMessageType,
+        ReturnCode::Application(code) => vsomeip_rs::ReturnCode::Application(code),
+        ReturnCode::Unknown => vsomeip_rs::ReturnCode::Unknown,
     }
 }
 
+fn message_type_from_vsomeip(value: vsomeip_rs::MessageType) -> MessageType {
+    match value {
+        vsomeip_rs::MessageType::Request => MessageType::Request,
+        vsomeip_rs::MessageType::RequestNoReturn => MessageType::RequestNoReturn,
+        vsomeip_rs::MessageType::Notification => MessageType::Notification,
+        vsomeip_rs::MessageType::RequestAck => MessageType::RequestAck,
+        vsomeip_rs::MessageType::RequestNoReturnAck => MessageType::RequestNoReturnAck,
+        vsomeip_rs::MessageType::NotificationAck => MessageType::NotificationAck,
+        vsomeip_rs::MessageType::Response => MessageType::Response,
+        vsomeip_rs::MessageType::Error => MessageType::Error,
+        vsomeip_rs::MessageType::ResponseAck => MessageType::ResponseAck,
+        vsomeip_rs::MessageType::ErrorAck => MessageType::ErrorAck,
+        vsomeip_rs::MessageType::Unknown => MessageType::Unknown,
+    }
+}
 
-impl Payload for vsomeip_rs::Payload {
-    type B = vsomeip_rs::Application;
-    
-    fn data(&self) -> &[u8] {
-        self.get_data()
+fn message_type_to_vsomeip(value: MessageType) -> vsomeip_rs::MessageType {
+    match value {
+        MessageType::Request => vsomeip_rs::MessageType::Request,
+        MessageType::RequestNoReturn => vsomeip_rs::MessageType::RequestNoReturn,
+        MessageType::Notification => vsomeip_rs::MessageType::Notification,
+        MessageType::RequestAck => vsomeip_rs::MessageType::RequestAck,
+        MessageType::RequestNoReturnAck => vsomeip_rs::MessageType::RequestNoReturnAck,
+        MessageType::NotificationAck => vsomeip_rs::MessageType::NotificationAck,
+        MessageType::Response => vsomeip_rs::MessageType::Response,
+        MessageType::Error => vsomeip_rs::MessageType::Error,
+        MessageType::ResponseAck => vsomeip_rs::MessageType::ResponseAck,
+        MessageType::ErrorAck => vsomeip_rs::MessageType::ErrorAck,
+        MessageType::Unknown => vsomeip_rs::MessageType::Unknown,
     }
+}
 
-    fn with_data(data: &[u8]) -> Self where Self: Sized {
-        vsomeip_rs::Payload::with_data(data)
+fn event_type_to_vsomeip(value: EventType) -> vsomeip_rs::EventType {
+    match value {
+        EventType::Event => vsomeip_rs::EventType::Event,
+        EventType::SelectiveEvent => vsomeip_rs::EventType::SelectiveEvent,
+        EventType::Field => vsomeip_rs::EventType::Field,
     }
 }
 
-impl Message for vsomeip_rs::Message {
-    type B = vsomeip_rs::Application;
-    
-    fn header(&self) -> &S {
-        // self.header()
+fn state_from_vsomeip(value: vsomeip_rs::State) -> State {
+    match value {
+        vsomeip_rs::State::Registered => State::Registered,
+        vsomeip_rs::State::Deregistered => State::Deregistered,
+    }
+}
+
+/// Copies a message received from vsomeip.
+fn from_vsomeip(message: &vsomeip_rs::Message) -> Message {
+    let mut converted = Message::new(message.is_reliable());
+
+    converted.set_service(message.get_service());
+    converted.set_instance(message.get_instance());
+    converted.set_method(message.get_method());
+    converted.set_client(message.get_client());
+    converted.set_session(message.get_session());
+    converted.set_interface_version(message.get_interface_version());
+    converted.set_message_type(message_type_from_vsomeip(message.get_message_type()));
+    converted.set_return_code(return_code_from_vsomeip(message.get_return_code()));
+    converted.set_payload(&Payload::with_data(message.get_payload().get_data()));
+
+    converted
+}
+
+/// Copies a message to send it with vsomeip.
+fn to_vsomeip(message: &Message) -> vsomeip_rs::Message {
+    let mut converted = vsomeip_rs::Message::new(message.is_reliable());
+
+    converted.set_service(message.get_service());
+    converted.set_instance(message.get_instance());
+    converted.set_method(message.get_method());
+    converted.set_client(message.get_client());
+    converted.set_session(message.get_session());
+    converted.set_interface_version(message.get_interface_version());
+    converted.set_message_type(message_type_to_vsomeip(message.get_message_type()));
+    converted.set_return_code(return_code_to_vsomeip(message.get_return_code()));
+    converted.set_payload(&vsomeip_rs::Payload::with_data(message.get_payload().get_data()));
+
+    converted
+}
+
+impl Application for VsomeIpApplication {
+    fn start(&self) {
+        self.application.start()
+    }
+    fn stop(&self) {
+        self.application.stop()
+    }
+    fn offer_service(&self, service_id: ServiceId, instance_id: InstanceId, major_version: MajorVersion, minor_version: MinorVersion) {
+        self.application.offer_service(service_id, instance_id, major_version, minor_version)
+    }
+    fn stop_offer_service(&self, service_id: ServiceId, instance_id: InstanceId, major_version: MajorVersion, minor_version: MinorVersion) {
+        self.application.stop_offer_service(service_id, instance_id, major_version, minor_version)
+    }
+    fn offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType) {
+        self.application.offer_event(service_id, instance_id, event_id, eventgroups, event_type_to_vsomeip(event_type))
+    }
+    fn stop_offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId) {
+        self.application.stop_offer_event(service_id, instance_id, event_id)
+    }
+    fn notify(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, payload: &Payload, force: bool) {
+        let payload = vsomeip_rs::Payload::with_data(payload.get_data());
+        self.application.notify(service_id, instance_id, event_id, &payload, force)
+    }
+    fn request_service(&self, service_id: ServiceId, instance_id: InstanceId, major_version: MajorVersion, minor_version: MinorVersion) {
+        self.application.request_service(service_id, instance_id, major_version, minor_version)
+    }
+    fn release_service(&self, service_id: ServiceId, instance_id: InstanceId) {
+        self.application.release_service(service_id, instance_id)
+    }
+    fn request_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType) {
+        self.application.request_event(service_id, instance_id, event_id, eventgroups, event_type_to_vsomeip(event_type))
+    }
+    fn release_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId) {
+        self.application.release_event(service_id, instance_id, event_id)
+    }
+    fn subscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, major_version: MajorVersion, event_id: EventId) {
+        self.application.subscribe(service_id, instance_id, eventgroup_id, major_version, event_id)
+    }
+    fn unsubscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, event_id: EventId) {
+        self.application.unsubscribe(service_id, instance_id, eventgroup_id, event_id)
+    }
+    fn send(&self, message: &mut Message) {
+        let converted = to_vsomeip(message);
+        self.application.send(&converted);
+
+        // vsomeip assigns the request id of requests while sending them
+        message.set_client(converted.get_client());
+        message.set_session(converted.get_session());
+    }
+    fn register_state_handler(&self, mut handler: StateHandler) {
+        self.application.register_state_handler(move |state| handler(state_from_vsomeip(state)))
+    }
+    fn register_message_handler(&self, service_id: ServiceId, instance_id: InstanceId, method_id: MethodId, mut handler: MessageHandler) {
+        self.application.register_message_handler(service_id, instance_id, method_id, move |message| {
+            handler(&from_vsomeip(message))
+        })
+    }
+    fn unregister_message_handler(&self, service_id: ServiceId, instance_id: InstanceId, method_id: MethodId) {
+        self.application.unregister_message_handler(service_id, instance_id, method_id)
+    }
+    fn register_availability_handler(&self, service_id: ServiceId, instance_id: InstanceId, handler: AvailabilityHandler, major_version: MajorVersion, minor_version: MinorVersion) {
+        self.application.register_availability_handler(service_id, instance_id, handler, major_version, minor_version)
     }
-    fn payload(&self) -> &vsomeip_rs::Payload {
-        self.get_payload()
+    fn clear_all_handlers(&self) {
+        self.application.clear_all_handlers()
     }
 }
@@ -1,60 +1,87 @@
-// mod implementations;
+mod message;
+mod implementations;
+
+use std::{fmt, sync::Arc};
 
-use std::net::IpAddr;
 use someip_types::*;
+use thiserror::Error;
+
+pub use message::*;
+pub use implementations::*;
+
+/// Method id matching every method and event of a service instance.
+pub const ANY_METHOD: MethodId = 0xFFFF;
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub enum ConnectionType {
-    #[default]
-    Tcp,
-    Udp
+pub type MessageHandler = Box<dyn FnMut(&Message) + Send>;
+pub type StateHandler = Box<dyn FnMut(State) + Send>;
+pub type AvailabilityHandler = Box<dyn FnMut(ServiceId, InstanceId, bool) + Send>;
+
+#[derive(Error, Debug)]
+#[error("Failed to create application {name}: {source}")]
+pub struct BackendError {
+    pub name: String,
+    #[source]
+    pub source: Box<dyn std::error::Error + Send + Sync>,
 }
 
-#[derive(Debug, Clone)]
+/// A service an application offers or uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ServiceConfig {
-    pub name: String,
-    pub id: ServiceId, 
+    pub id: ServiceId,
     pub instance_id: InstanceId,
-    pub address: IpAddr,
     pub major_version: MajorVersion,
     pub minor_version: MinorVersion,
-    pub port: u16,
-    pub connection_type: ConnectionType
+    /// Where the service is offered, or reached if the backend can't discover it.
+    pub conn_type: ConnectionType,
 }
 
-pub trait Service {
-    type B: Backend;
-    fn with_method<F>(&mut self, method_id: MethodId, handler: F) where F: FnMut(<Self::B as Backend>::M) + 'static;
+/// Describes an application to the [`Backend`] creating it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplicationConfig {
+    pub name: String,
+    pub id: u16,
+    pub services: Vec<ServiceConfig>,
 }
 
-pub trait Message {
-    type B: Backend;
-    fn header(&self) -> &SOMEIpHeader;
-    fn payload(&self) -> &<Self::B as Backend>::P;
+/// A SOME/IP implementation the runtime and clients of smip run on.
+///
+/// Each runtime and client creates its own [`Application`] with it. They keep the
+/// backend as an `Arc<dyn Backend>`, so any implementation can be chosen at runtime
+/// without changing their types.
+pub trait Backend: fmt::Debug + Send + Sync + 'static {
+    /// Creates an application, `config` names it and lists the services it offers or uses.
+    fn create_application(&self, config: &ApplicationConfig) -> Result<Arc<dyn Application>, BackendError>;
 }
 
-pub trait Payload {
-    type B: Backend;
-    fn with_data(data: &[u8]) -> Self where Self: Sized;
-    fn data(&self) -> &[u8];
-}
+/// The operations a runtime or client needs from its connection to the backend.
+///
+/// Handlers are called on the application's own dispatcher thread, which runs for
+/// as long as [`Application::start`] blocks.
+pub trait Application: Send + Sync + 'static {
+    /// Dispatches messages and events until the application is stopped.
+    fn start(&self);
+    fn stop(&self);
 
-pub trait Backend {
-    type S: Service;
-    type M: Message;
-    type P: Payload;
-    fn init(name: impl AsRef<str>) -> Self;
-    fn create_service(config: &ServiceConfig) -> Self::S;
-    fn offer_service(&self, service: &Self::S);
-    fn create_payload(data: &[u8]) -> Self::P;
-    fn create_message(header: &SOMEIpHeader, reliable: bool, payload: Self::P) -> Self::M;
-}
+    fn offer_service(&self, service_id: ServiceId, instance_id: InstanceId, major_version: MajorVersion, minor_version: MinorVersion);
+    fn stop_offer_service(&self, service_id: ServiceId, instance_id: InstanceId, major_version: MajorVersion, minor_version: MinorVersion);
+    fn offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType);
+    fn stop_offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId);
+    fn notify(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, payload: &Payload, force: bool);
 
+    fn request_service(&self, service_id: ServiceId, instance_id: InstanceId, major_version: MajorVersion, minor_version: MinorVersion);
+    fn release_service(&self, service_id: ServiceId, instance_id: InstanceId);
+    fn request_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType);
+    fn release_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId);
+    fn subscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, major_version: MajorVersion, event_id: EventId);
+    fn unsubscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, event_id: EventId);
 
-// vsomeip-rs
-// application.init()
+    /// Sends a message, requests get the client and session id they're sent with assigned.
+    fn send(&self, message: &mut Message);
 
-
-// let service1 = Service::new(config).method(method_id, |message| {...});
-// backend.offer_service(service1);
-// backend.start();
\ No newline at end of file
+    fn register_state_handler(&self, handler: StateHandler);
+    /// `method_id` can be [`ANY_METHOD`] to receive all messages of the service instance.
+    fn register_message_handler(&self, service_id: ServiceId, instance_id: InstanceId, method_id: MethodId, handler: MessageHandler);
+    fn unregister_message_handler(&self, service_id: ServiceId, instance_id: InstanceId, method_id: MethodId);
+    fn register_availability_handler(&self, service_id: ServiceId, instance_id: InstanceId, handler: AvailabilityHandler, major_version: MajorVersion, minor_version: MinorVersion);
+    fn clear_all_handlers(&self);
+}
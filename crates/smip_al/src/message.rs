@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use someip_types::*;
+
+/// Version of the SOME/IP protocol the messages follow.
+pub const PROTOCOL_VERSION: ProtocolVersion = 0x01;
+
+/// Payload of a [`Message`], clones share the same data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Payload {
+    data: Arc<[u8]>,
+}
+
+impl Payload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_data(data: &[u8]) -> Self {
+        Self {
+            data: data.into(),
+        }
+    }
+    pub fn set_data(&mut self, data: &[u8]) {
+        self.data = data.into();
+    }
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A SOME/IP message, independent of the transport it's sent over.
+///
+/// Mirrors the message of vsomeip, the header fields are read and written with
+/// the `get_`/`set_` methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    service: ServiceId,
+    instance: InstanceId,
+    method: MethodId,
+    client: ClientId,
+    session: SessionId,
+    interface_version: InterfaceVersion,
+    message_type: MessageType,
+    return_code: ReturnCode,
+    reliable: bool,
+    payload: Payload,
+}
+
+impl Message {
+    pub fn new(reliable: bool) -> Self {
+        Self {
+            service: 0,
+            instance: 0,
+            method: 0,
+            client: 0,
+            session: 0,
+            interface_version: 0,
+            message_type: MessageType::Unknown,
+            return_code: ReturnCode::Ok,
+            reliable,
+            payload: Payload::new(),
+        }
+    }
+    pub fn request(reliable: bool) -> Self {
+        Self {
+            message_type: MessageType::Request,
+            ..Self::new(reliable)
+        }
+    }
+    /// A response to `request`, sent back to the client it came from.
+    pub fn response(request: &Message) -> Self {
+        Self {
+            service: request.service,
+            instance: request.instance,
+            method: request.method,
+            client: request.client,
+            session: request.session,
+            interface_version: request.interface_version,
+            message_type: MessageType::Response,
+            return_code: ReturnCode::Ok,
+            reliable: request.reliable,
+            payload: Payload::new(),
+        }
+    }
+
+    pub fn get_service(&self) -> ServiceId {
+        self.service
+    }
+    pub fn set_service(&mut self, service_id: ServiceId) {
+        self.service = service_id;
+    }
+    pub fn get_instance(&self) -> InstanceId {
+        self.instance
+    }
+    pub fn set_instance(&mut self, instance_id: InstanceId) {
+        self.instance = instance_id;
+    }
+    pub fn get_method(&self) -> MethodId {
+        self.method
+    }
+    pub fn set_method(&mut self, method_id: MethodId) {
+        self.method = method_id;
+    }
+    pub fn get_client(&self) -> ClientId {
+        self.client
+    }
+    pub fn set_client(&mut self, client_id: ClientId) {
+        self.client = client_id;
+    }
+    pub fn get_session(&self) -> SessionId {
+        self.session
+    }
+    pub fn set_session(&mut self, session_id: SessionId) {
+        self.session = session_id;
+    }
+    /// The client and session id, which identify a request and its response.
+    pub fn get_request(&self) -> RequestId {
+        ((self.client as RequestId) << 16) | self.session as RequestId
+    }
+    pub fn get_interface_version(&self) -> InterfaceVersion {
+        self.interface_version
+    }
+    pub fn set_interface_version(&mut self, interface_version: InterfaceVersion) {
+        self.interface_version = interface_version;
+    }
+    pub fn get_protocol_version(&self) -> ProtocolVersion {
+        PROTOCOL_VERSION
+    }
+    pub fn get_message_type(&self) -> MessageType {
+        self.message_type
+    }
+    pub fn set_message_type(&mut self, message_type: MessageType) {
+        self.message_type = message_type;
+    }
+    pub fn get_return_code(&self) -> ReturnCode {
+        self.return_code
+    }
+    pub fn set_return_code(&mut self, return_code: ReturnCode) {
+        self.return_code = return_code;
+    }
+    pub fn is_reliable(&self) -> bool {
+        self.reliable
+    }
+    pub fn set_reliable(&mut self, reliable: bool) {
+        self.reliable = reliable;
+    }
+    pub fn get_payload(&self) -> Payload {
+        self.payload.clone()
+    }
+    pub fn set_payload(&mut self, payload: &Payload) {
+        self.payload = payload.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_response() {
+        let mut request = Message::request(false);
+        request.set_service(0x1234);
+        request.set_instance(0x0001);
+        request.set_method(0x0002);
+        request.set_client(0x0010);
+        request.set_session(0x0020);
+        request.set_payload(&Payload::with_data(&[1, 2, 3]));
+
+        assert_eq!(request.get_message_type(), MessageType::Request);
+        assert_eq!(request.get_request(), 0x0010_0020);
+
+        let response = Message::response(&request);
+
+        assert_eq!(response.get_message_type(), MessageType::Response);
+        assert_eq!(response.get_request(), request.get_request());
+        assert_eq!(response.get_method(), 0x0002);
+        assert!(!response.is_reliable());
+        assert!(response.get_payload().get_data().is_empty());
+    }
+}
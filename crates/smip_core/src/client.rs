@@ -1,177 +1,448 @@
-use std::{sync::{mpsc, Arc}, thread};
-
-use crate::{error::SmipError, types::*};
-use parking_lot::{Condvar, Mutex};
-use vsomeip_compat::{set_vsomeip_config, VsomeIpConfig};
-use vsomeip_rs::{
-    Application, InstanceId, MessageType, MethodId, Runtime, ServiceId, ANY_METHOD
-};
-
-enum MessageCommand {
-    SendMessage(Message),
-    ReceiveMessage(Message),
-    Exit
-}
-pub struct Client {
+use std::{collections::HashMap, future::Future, marker::PhantomData, panic::{self, AssertUnwindSafe}, sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc}, time::{Duration, Instant}};
+
+use crate::{codec::*, error::SmipError, options::CallOptions, panic::panic_message, pending::PendingRequests, subscription::*, telemetry, trace, types::*};
+use futures::future::{self, Either};
+use futures_timer::Delay;
+use serde::{de::DeserializeOwned, Serialize};
+use parking_lot::Mutex;
+use tracing::{Instrument, Span};
+use smip_al::{ApplicationConfig, Backend, ANY_METHOD};
+use someip_types::{EventGroupId, EventId, EventType, InstanceId, MajorVersion, MessageType, MethodId, ReturnCode, ServiceId};
+#[cfg(feature = "vsomeip")]
+use smip_al::VsomeIp;
+#[cfg(feature = "vsomeip")]
+use vsomeip_compat::VsomeIpConfig;
+
+type NotificationHandler = Arc<Mutex<dyn FnMut(&Message) + Send>>;
+/// Handlers of each event, with the token of the subscription they belong to.
+type Subscriptions = HashMap<EventId, Vec<(u64, NotificationHandler)>>;
+
+/// Client for a single service, payloads are encoded with the codec `C`
+/// which has to match the one used by the service.
+///
+/// Requests can be sent from several threads or tasks at once, each response
+/// is matched to its request by the request id.
+pub struct Client<C: PayloadCodec = SomeIpCodec> {
     application: Application,
-    sender_join: Option<std::thread::JoinHandle<()>>,
     app_join: Option<std::thread::JoinHandle<()>>,
-    message_receiver: mpsc::Receiver<MessageCommand>,
-    message_sender: mpsc::Sender<MessageCommand>,
+    pending: Arc<PendingRequests>,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    next_subscription: AtomicU64,
     service_id: ServiceId,
-    instance_id: InstanceId
+    instance_id: InstanceId,
+    major_version: MajorVersion,
+    options: CallOptions,
+    _codec: PhantomData<fn() -> C>
 }
 
-impl Client {
-    fn sender_thread(pair: Arc<(Mutex<bool>, Condvar)>, message_receiver: mpsc::Receiver<MessageCommand>, service: ServiceId, instance: InstanceId, application: Application){
-        let &(ref lock, ref cvar) = &*pair;
-        let mut started = lock.lock();
-        if !*started {
-            cvar.wait(&mut started);
+pub struct ClientBuilder<C: PayloadCodec = SomeIpCodec> {
+    config: ApplicationConfig,
+    options: CallOptions,
+    backend: Option<Arc<dyn Backend>>,
+    _codec: PhantomData<fn() -> C>
+}
+
+impl ClientBuilder {
+    /// A client for the service of a vsomeip configuration, reached with vsomeip
+    /// and the settings of `config` unless another backend is set.
+    #[cfg(feature = "vsomeip")]
+    pub fn new(config: &VsomeIpConfig) -> Self {
+        Self::with_application(config.into()).backend(VsomeIp::new(config.clone()))
+    }
+    /// A client for the only service of `config`.
+    ///
+    /// Without the `vsomeip` feature a [`backend`](Self::backend) has to be set.
+    pub fn with_application(config: ApplicationConfig) -> Self {
+        Self {
+            config,
+            options: CallOptions::default(),
+            #[cfg(feature = "vsomeip")]
+            backend: Some(Arc::new(VsomeIp::default())),
+            #[cfg(not(feature = "vsomeip"))]
+            backend: None,
+            _codec: PhantomData
         }
+    }
+}
 
-        for command in message_receiver.iter() {
-            match command {
-                MessageCommand::SendMessage(message) => {  
-                    if message.get_service() == service && message.get_instance() == instance {
-                        application.send(&message);
-                    }
-                },
-                MessageCommand::Exit => {
-                    println!("Recieved exit command");
-                    return;
-                },
-                _=> {
-                    unreachable!("Unexpected message")
-                }
-            }
+impl<C: PayloadCodec> ClientBuilder<C> {
+    pub fn codec<Codec: PayloadCodec>(self) -> ClientBuilder<Codec> {
+        ClientBuilder {
+            config: self.config,
+            options: self.options,
+            backend: self.backend,
+            _codec: PhantomData
         }
     }
-    pub fn new(config: &VsomeIpConfig) -> anyhow::Result<Self> {
-        let runtime = Runtime::get();
+    /// Default options for calls that don't specify their own.
+    pub fn call_options(mut self, options: CallOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// Sets the SOME/IP implementation to reach the service with, vsomeip by default.
+    ///
+    /// Any [`Backend`] can be used, the client keeps it as an `Arc<dyn Backend>`.
+    pub fn backend(mut self, backend: impl Backend) -> Self {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+    pub fn build(self) -> anyhow::Result<Client<C>> {
+        let Some(backend) = self.backend else {
+            anyhow::bail!("No backend is set for client {}", self.config.name);
+        };
 
-        let config_str = config.clone().build();
-        let application = runtime.create_application_with(&config.app_id.0, |_app| {
-            set_vsomeip_config(&config_str);
-        })?;
+        Client::create(&self.config, self.options, &*backend)
+    }
+}
 
-        let (sender, receiver) = mpsc::channel();
-        let (message_sender, message_receiver) = mpsc::channel::<MessageCommand>();
+/// Removes the request from the pending table once its caller stops waiting,
+/// including when the future is dropped before completing.
+struct PendingGuard<'a> {
+    pending: &'a PendingRequests,
+    token: u64,
+}
 
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.cancel(self.token);
+    }
+}
+
+#[cfg(feature = "vsomeip")]
+impl Client {
+    pub fn new(config: &VsomeIpConfig) -> anyhow::Result<Self> {
+        Self::builder(config).build()
+    }
+    pub fn builder(config: &VsomeIpConfig) -> ClientBuilder {
+        ClientBuilder::new(config)
+    }
+}
+
+impl<C: PayloadCodec> Client<C> {
+    #[cfg(feature = "vsomeip")]
+    pub fn with_codec(config: &VsomeIpConfig) -> anyhow::Result<Self> {
+        ClientBuilder::new(config).codec::<C>().build()
+    }
+    fn create(config: &ApplicationConfig, options: CallOptions, backend: &dyn Backend) -> anyhow::Result<Self> {
+        let application = backend.create_application(config)?;
 
         assert!(config.services.len() == 1);
         let service_id = config.services[0].id;
-        let instance_id = config.instance_id;
+        let instance_id = config.services[0].instance_id;
         let major_version = config.services[0].major_version;
         let minor_version = config.services[0].minor_version;
-    
+
+        let pending = Arc::new(PendingRequests::new(application.clone()));
+        let pending_clone = pending.clone();
+
+        let subscriptions = Arc::new(Mutex::new(Subscriptions::new()));
+        let subscriptions_clone = subscriptions.clone();
+
         application.register_message_handler(
             service_id,
             instance_id,
             ANY_METHOD,
-            move |message| {
-                if message.get_message_type() == MessageType::Response {
-                    sender
-                        .send(MessageCommand::ReceiveMessage(message.clone()))
-                        .unwrap();
+            Box::new(move |message| {
+                match message.get_message_type() {
+                    MessageType::Response | MessageType::Error => {
+                        // Handlers are called by the backend, a panic must not unwind into it
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| pending_clone.complete(message)));
+                        if let Err(panic) = result {
+                            tracing::error!("Completing request {:#010x} panicked: {}", message.get_request(), panic_message(&*panic));
+                        }
+                    },
+                    MessageType::Notification => {
+                        // Callbacks run without holding the subscriptions, so they can subscribe and unsubscribe
+                        let handlers = subscriptions_clone.lock().get(&message.get_method()).cloned().unwrap_or_default();
+                        if handlers.is_empty() {
+                            return;
+                        }
+
+                        let _span = trace::notification_span(message).entered();
+                        for (_, handler) in handlers {
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| (handler.lock())(message)));
+                            if let Err(panic) = result {
+                                tracing::error!("Callback for event {:#06x} panicked: {}", message.get_method(), panic_message(&*panic));
+                            }
+                        }
+                    },
+                    _ => {}
                 }
-            },
+            }),
         );
-    let application_clone1 = application.clone();
-
-    application.request_service(service_id, instance_id, major_version, minor_version);
-
-    let pair = Arc::new((Mutex::new(false), Condvar::new()));
-
-    let pair_clone = pair.clone();
-    
-    
-    let application_clone = application.clone();
-    let sender_join = thread::spawn(move || {
-        Self::sender_thread(pair, message_receiver, service_id, instance_id, application_clone1);
-    });
-
-
-    application.register_availability_handler(service_id, instance_id, move |_service, _instance, _is_available| {
-        let &(ref lock, ref cvar) = &*pair_clone;
-        let mut started = lock.lock();
-        *started = true;
-        cvar.notify_one();
-    }, major_version, minor_version);
-
-    let app_join = std::thread::spawn(move || application_clone.start());
-    Ok(Self {
-        application,
-        service_id,
-        instance_id,
-        sender_join: Some(sender_join),
-        app_join: Some(app_join),
-        message_receiver: receiver,
-        message_sender
-    })
-}
-pub fn send<T: ToPayload, R: for<'a> FromPayload<'a>>(
-    &self,
-    method_id: MethodId,
-    data: T,
-) -> Result<R, SmipError> {
-        let mut message = Message::request(true);
+
+        application.request_service(service_id, instance_id, major_version, minor_version);
+
+        // Requests are queued until the service is available
+        let pending_clone = pending.clone();
+        application.register_availability_handler(service_id, instance_id, Box::new(move |_service, _instance, is_available| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| pending_clone.set_available(is_available)));
+            if let Err(panic) = result {
+                tracing::error!("Availability handler of service {:#06x} instance {:#06x} panicked: {}", service_id, instance_id, panic_message(&*panic));
+            }
+        }), major_version, minor_version);
+
+        let application_clone = application.clone();
+        let app_join = std::thread::spawn(move || application_clone.start());
+
+        Ok(Self {
+            application,
+            service_id,
+            instance_id,
+            major_version,
+            options,
+            pending,
+            subscriptions,
+            next_subscription: AtomicU64::new(0),
+            app_join: Some(app_join),
+            _codec: PhantomData
+        })
+    }
+    pub fn service_id(&self) -> ServiceId {
+        self.service_id
+    }
+    pub fn instance_id(&self) -> InstanceId {
+        self.instance_id
+    }
+    /// Options used by the calls that don't take their own.
+    pub fn options(&self) -> &CallOptions {
+        &self.options
+    }
+    fn request<T: Serialize>(&self, method_id: MethodId, data: &T, options: &CallOptions) -> Result<Message, SmipError> {
+        let mut message = Message::request(options.reliable);
 
         message.set_service(self.service_id);
         message.set_instance(self.instance_id);
         message.set_method(method_id);
 
-        let payload = Payload::with_data(&data.to_payload()?);
-
+        let payload = Payload::with_data(&C::encode(data)?);
         message.set_payload(&payload);
 
-        let response = self.send_raw(message)?;
+        Ok(message)
+    }
+    pub fn send<T: Serialize, R: DeserializeOwned>(&self, method_id: MethodId, data: T) -> Result<R, SmipError> {
+        self.send_with(method_id, data, &self.options)
+    }
+    /// Blocks until the response arrives, async code has to use [`Self::send_async_with`].
+    ///
+    /// Fails with [`SmipError::BlockingInAsync`] on a thread that is running an
+    /// executor of the futures crate, like the runtime's thread pool.
+    pub fn send_with<T: Serialize, R: DeserializeOwned>(&self, method_id: MethodId, data: T, options: &CallOptions) -> Result<R, SmipError> {
+        block_on(self.send_async_with(method_id, data, options))
+    }
+    pub async fn send_async<T: Serialize, R: DeserializeOwned>(&self, method_id: MethodId, data: T) -> Result<R, SmipError> {
+        self.send_async_with(method_id, data, &self.options).await
+    }
+    pub async fn send_async_with<T: Serialize, R: DeserializeOwned>(&self, method_id: MethodId, data: T, options: &CallOptions) -> Result<R, SmipError> {
+        let message = self.request(method_id, &data, options)?;
+
+        let response = self.send_raw_async_with(message, options).await?;
         let response_payload = response.get_payload();
-        
-        return R::from_payload(response_payload.get_data());
+
+        C::decode(response_payload.get_data())
+    }
+    /// Sends a `RequestNoReturn` request and returns once it is handed to vsomeip,
+    /// without waiting for the service to handle it.
+    pub fn fire<T: Serialize>(&self, method_id: MethodId, data: T) -> Result<(), SmipError> {
+        self.fire_with(method_id, data, &self.options)
+    }
+    /// Only `options.reliable` applies, fire and forget requests are never repeated.
+    pub fn fire_with<T: Serialize>(&self, method_id: MethodId, data: T, options: &CallOptions) -> Result<(), SmipError> {
+        let mut message = self.request(method_id, &data, options)?;
+        message.set_message_type(MessageType::RequestNoReturn);
+
+        let _span = trace::call_span(&message).entered();
+        telemetry::call_sent(&message);
+        self.pending.send_no_return(message);
+
+        Ok(())
     }
     pub fn send_raw(&self, message: Message) -> Result<Message, SmipError> {
-        let req_method_id = message.get_method();
-        self.application.send(&message);
-        self.message_sender.send(MessageCommand::SendMessage(message)).unwrap();
+        self.send_raw_with(message, &self.options)
+    }
+    /// Sends a prepared request, `options.reliable` is ignored in favour of the message's own setting.
+    ///
+    /// Blocks like [`Self::send_with`].
+    pub fn send_raw_with(&self, message: Message, options: &CallOptions) -> Result<Message, SmipError> {
+        block_on(self.send_raw_async_with(message, options))
+    }
+    pub async fn send_raw_async(&self, message: Message) -> Result<Message, SmipError> {
+        self.send_raw_async_with(message, &self.options).await
+    }
+    pub async fn send_raw_async_with(&self, message: Message, options: &CallOptions) -> Result<Message, SmipError> {
+        let span = trace::call_span(&message);
+
+        telemetry::call_sent(&message);
+        let started = Instant::now();
+
+        let result = self.send_raw_attempts(&message, options, &span).instrument(span.clone()).await;
+        telemetry::call_completed(&message, &result, started);
+
+        result
+    }
+    async fn send_raw_attempts(&self, message: &Message, options: &CallOptions, span: &Span) -> Result<Message, SmipError> {
+        let mut backoff = options.backoff;
+        let mut attempt = 0;
 
-        const RESPONSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
-        
         loop {
-            let result = self.message_receiver.recv_timeout(RESPONSE_TIMEOUT);
-        
+            let result = match options.attempt_timeout() {
+                Ok(timeout) => self.attempt(message.clone(), timeout).await,
+                Err(err) => return Err(err),
+            };
+
+            if let Err(SmipError::Timeout) = result {
+                telemetry::attempt_timed_out(message);
+            }
+
             match result {
-                Ok(MessageCommand::ReceiveMessage(message)) => {
-                    let service_id = message.get_service();
-                    let instance_id = message.get_instance();
-                    let method_id = message.get_method();
-        
-                    if service_id == self.service_id && instance_id == self.instance_id && method_id == req_method_id {
-                        return Ok(message);
-                    } else {
-                        continue;
-                    }
+                Err(err) if err.is_retryable() && attempt < options.retries => {
+                    Delay::new(options.retry_delay(backoff)).await;
+
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
                 },
-                Err(_timeout) => {
-                    return Err(SmipError::NoResponse);
-                }
-                _=> {
-                    unreachable!("Unexpected message")
+                result => {
+                    span.record("attempts", attempt + 1);
+                    return result;
                 },
             }
         }
     }
+    async fn attempt(&self, message: Message, timeout: Duration) -> Result<Message, SmipError> {
+        let (token, receiver) = self.pending.send(message);
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            token,
+        };
+
+        let response = match future::select(receiver, Delay::new(timeout)).await {
+            Either::Left((Ok(response), _)) => response?,
+            Either::Left((Err(_canceled), _)) => return Err(SmipError::ServiceUnavailable),
+            Either::Right(_) if self.pending.is_queued(token) => return Err(SmipError::ServiceUnavailable),
+            Either::Right(_) => return Err(SmipError::Timeout),
+        };
+
+        trace::record_return_code(&Span::current(), response.get_return_code());
+
+        if response.get_message_type() != MessageType::Error && response.get_return_code() == ReturnCode::Ok {
+            return Ok(response);
+        }
+
+        match response.get_return_code() {
+            code @ (ReturnCode::NotReachable | ReturnCode::Timeout | ReturnCode::NotReady | ReturnCode::UnknownService) => {
+                Err(SmipError::Transport(code))
+            },
+            code => Err(SmipError::Remote {
+                code,
+                payload: response.get_payload().get_data().to_vec(),
+            }),
+        }
+    }
+    pub fn subscribe<T, F>(&self, eventgroup: EventGroupId, event: EventId, callback: F) -> Subscription<'_, C>
+    where
+        T: DeserializeOwned,
+        F: FnMut(Result<T, SmipError>) + Send + 'static,
+    {
+        self.subscribe_as(eventgroup, event, EventType::Event, callback)
+    }
+    pub fn subscribe_iter<T>(&self, eventgroup: EventGroupId, event: EventId) -> EventReceiver<'_, T, C>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self.subscribe_iter_as(eventgroup, event, EventType::Event)
+    }
+    /// Subscribes to the notifier of a field, the callback receives the current
+    /// value of the field right away and every change after that.
+    pub fn subscribe_field<T, F>(&self, eventgroup: EventGroupId, notifier: EventId, callback: F) -> Subscription<'_, C>
+    where
+        T: DeserializeOwned,
+        F: FnMut(Result<T, SmipError>) + Send + 'static,
+    {
+        self.subscribe_as(eventgroup, notifier, EventType::Field, callback)
+    }
+    pub fn subscribe_field_iter<T>(&self, eventgroup: EventGroupId, notifier: EventId) -> EventReceiver<'_, T, C>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self.subscribe_iter_as(eventgroup, notifier, EventType::Field)
+    }
+    fn subscribe_as<T, F>(&self, eventgroup: EventGroupId, event: EventId, event_type: EventType, mut callback: F) -> Subscription<'_, C>
+    where
+        T: DeserializeOwned,
+        F: FnMut(Result<T, SmipError>) + Send + 'static,
+    {
+        let handler = move |message: &Message| {
+            let payload = message.get_payload();
+            (callback)(C::decode(payload.get_data()));
+        };
+        let token = self.next_subscription.fetch_add(1, Ordering::Relaxed);
+        self.subscriptions.lock().entry(event).or_default().push((token, Arc::new(Mutex::new(handler))));
+
+        self.application.request_event(self.service_id, self.instance_id, event, &[eventgroup], event_type);
+        self.application.subscribe(self.service_id, self.instance_id, eventgroup, self.major_version, event);
+
+        Subscription {
+            client: self,
+            eventgroup,
+            event,
+            token,
+        }
+    }
+    fn subscribe_iter_as<T>(&self, eventgroup: EventGroupId, event: EventId, event_type: EventType) -> EventReceiver<'_, T, C>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        let subscription = self.subscribe_as(eventgroup, event, event_type, move |value| {
+            let _ = sender.send(value);
+        });
+
+        EventReceiver {
+            subscription,
+            receiver,
+        }
+    }
+    /// Removes the callback of the subscription `token`, the event is only
+    /// unsubscribed once no other subscription of this client uses it.
+    pub(crate) fn unsubscribe(&self, eventgroup: EventGroupId, event: EventId, token: u64) {
+        let last = {
+            let mut subscriptions = self.subscriptions.lock();
+            let Some(handlers) = subscriptions.get_mut(&event) else {
+                return;
+            };
+            handlers.retain(|(t, _)| *t != token);
+
+            let last = handlers.is_empty();
+            if last {
+                subscriptions.remove(&event);
+            }
+            last
+        };
+
+        if last {
+            self.application.unsubscribe(self.service_id, self.instance_id, eventgroup, event);
+            self.application.release_event(self.service_id, self.instance_id, event);
+        }
+    }
+}
+
+/// Runs a call to completion, unless the thread already runs an executor which
+/// would make `futures::executor::block_on` panic.
+fn block_on<T>(call: impl Future<Output = Result<T, SmipError>>) -> Result<T, SmipError> {
+    if futures::executor::enter().is_err() {
+        return Err(SmipError::BlockingInAsync);
+    }
+
+    futures::executor::block_on(call)
 }
 
-impl Drop for Client {
+impl<C: PayloadCodec> Drop for Client<C> {
     fn drop(&mut self) {
         self.application.clear_all_handlers();
         self.application.release_service(self.service_id, self.instance_id);
-        self.message_sender.send(MessageCommand::Exit).unwrap();
-        self.sender_join.take().unwrap().join().unwrap();
-        println!("Sender thread exited");
         self.application.stop();
         self.app_join.take().unwrap().join().unwrap();
-        println!("App thread exited");
+        tracing::debug!("App thread exited");
     }
 }
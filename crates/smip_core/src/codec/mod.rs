@@ -0,0 +1,90 @@
+mod raw;
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::SmipError, serializer};
+
+/// Encoding used for the payloads of a service's methods, events and fields,
+/// selected with `#[service(codec = ...)]`.
+pub trait PayloadCodec: Send + Sync + 'static {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SmipError>;
+    fn decode<'de, T: Deserialize<'de>>(payload: &'de [u8]) -> Result<T, SmipError>;
+}
+
+/// SOME/IP serialization, see [`serializer`](crate::serializer). This is the default codec.
+pub struct SomeIpCodec;
+
+impl PayloadCodec for SomeIpCodec {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SmipError> {
+        serializer::to_vec(value).map_err(|err| SmipError::ToPayloadError(err.into()))
+    }
+    fn decode<'de, T: Deserialize<'de>>(payload: &'de [u8]) -> Result<T, SmipError> {
+        serializer::from_slice(payload).map_err(|err| SmipError::FromPayloadError(err.into()))
+    }
+}
+
+/// Bincode with fixed size integers, for peers that are also written using smip.
+pub struct BincodeCodec;
+
+impl PayloadCodec for BincodeCodec {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SmipError> {
+        bincode::serialize(value).map_err(|err| SmipError::ToPayloadError(err.into()))
+    }
+    fn decode<'de, T: Deserialize<'de>>(payload: &'de [u8]) -> Result<T, SmipError> {
+        let mut de = bincode::Deserializer::from_slice(
+            payload,
+            bincode::options().with_fixint_encoding().allow_trailing_bytes(),
+        );
+
+        T::deserialize(&mut de).map_err(|err| SmipError::FromPayloadError(err.into()))
+    }
+}
+
+/// Passes the payload through untouched, only `&[u8]`, `Vec<u8>`, `&str`,
+/// `String` and `()` can be used as arguments and return values.
+pub struct RawCodec;
+
+impl PayloadCodec for RawCodec {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SmipError> {
+        raw::to_vec(value).map_err(|err| SmipError::ToPayloadError(err.into()))
+    }
+    fn decode<'de, T: Deserialize<'de>>(payload: &'de [u8]) -> Result<T, SmipError> {
+        raw::from_slice(payload).map_err(|err| SmipError::FromPayloadError(err.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_codec() {
+        assert_eq!(RawCodec::encode(&vec![1u8, 2, 3]).unwrap(), [1, 2, 3]);
+        assert_eq!(RawCodec::encode("abc").unwrap(), b"abc");
+        assert!(RawCodec::encode(&()).unwrap().is_empty());
+        assert!(RawCodec::encode(&1u32).is_err());
+
+        let payload = [0xDE, 0xAD];
+        assert_eq!(RawCodec::decode::<&[u8]>(&payload).unwrap(), payload);
+        assert_eq!(RawCodec::decode::<Vec<u8>>(&payload).unwrap(), payload);
+        assert_eq!(RawCodec::decode::<String>(b"abc").unwrap(), "abc");
+        assert!(RawCodec::decode::<u16>(&payload).is_err());
+    }
+
+    #[test]
+    fn test_bincode_codec() {
+        let payload = BincodeCodec::encode(&(1u16, "a".to_string())).unwrap();
+
+        assert_eq!(payload, [1, 0, 1, 0, 0, 0, 0, 0, 0, 0, b'a']);
+        assert_eq!(BincodeCodec::decode::<(u16, String)>(&payload).unwrap(), (1, "a".to_string()));
+    }
+
+    #[test]
+    fn test_borrowed_arguments() {
+        let payload = SomeIpCodec::encode(&("abc", 7u8, &[1u8, 2][..])).unwrap();
+
+        let (text, value, data) = SomeIpCodec::decode::<(&str, u8, &[u8])>(&payload).unwrap();
+        assert_eq!((text, value, data), ("abc", 7, &[1, 2][..]));
+    }
+}
@@ -0,0 +1,401 @@
+use serde::{
+    de::{self, Visitor},
+    ser::{self, Error as _, Impossible},
+    Deserialize, Serialize,
+};
+
+use crate::serializer::Error;
+
+pub(crate) fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    value.serialize(&mut RawSerializer { output: &mut output })?;
+
+    Ok(output)
+}
+
+pub(crate) fn from_slice<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T, Error> {
+    T::deserialize(RawDeserializer { input })
+}
+
+fn unsupported(kind: &str) -> Error {
+    Error::custom(format!("raw payloads can not contain a {}", kind))
+}
+
+struct RawSerializer<'a> {
+    output: &'a mut Vec<u8>,
+}
+
+/// Collects the elements of a `Vec<u8>`, which serde serializes as a sequence.
+struct ByteSeq<'a> {
+    output: &'a mut Vec<u8>,
+}
+
+impl<'a> ser::Serializer for &'a mut RawSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = ByteSeq<'a>;
+    type SerializeTuple = ByteSeq<'a>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), Error> {
+        Err(unsupported("bool"))
+    }
+    fn serialize_i8(self, _v: i8) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_i16(self, _v: i16) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_i32(self, _v: i32) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_i64(self, _v: i64) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u8(self, _v: u8) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u16(self, _v: u16) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u32(self, _v: u32) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u64(self, _v: u64) -> Result<(), Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_f32(self, _v: f32) -> Result<(), Error> {
+        Err(unsupported("float"))
+    }
+    fn serialize_f64(self, _v: f64) -> Result<(), Error> {
+        Err(unsupported("float"))
+    }
+    fn serialize_char(self, _v: char) -> Result<(), Error> {
+        Err(unsupported("char"))
+    }
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.output.extend_from_slice(v.as_bytes());
+        Ok(())
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.output.extend_from_slice(v);
+        Ok(())
+    }
+    fn serialize_none(self) -> Result<(), Error> {
+        Err(unsupported("option"))
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<(), Error> {
+        Err(unsupported("option"))
+    }
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str) -> Result<(), Error> {
+        Err(unsupported("enum"))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported("enum"))
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ByteSeq<'a>, Error> {
+        Ok(ByteSeq { output: self.output })
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<ByteSeq<'a>, Error> {
+        Ok(ByteSeq { output: self.output })
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("struct"))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("enum"))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("map"))
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported("struct"))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("enum"))
+    }
+}
+
+impl ByteSeq<'_> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let byte = value.serialize(ByteSerializer)?;
+        self.output.push(byte);
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for ByteSeq<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for ByteSeq<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Accepts only a single `u8`, used for the elements of byte sequences.
+struct ByteSerializer;
+
+impl ser::Serializer for ByteSerializer {
+    type Ok = u8;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<u8, Error>;
+    type SerializeTuple = Impossible<u8, Error>;
+    type SerializeTupleStruct = Impossible<u8, Error>;
+    type SerializeTupleVariant = Impossible<u8, Error>;
+    type SerializeMap = Impossible<u8, Error>;
+    type SerializeStruct = Impossible<u8, Error>;
+    type SerializeStructVariant = Impossible<u8, Error>;
+
+    fn serialize_u8(self, v: u8) -> Result<u8, Error> {
+        Ok(v)
+    }
+    fn serialize_bool(self, _v: bool) -> Result<u8, Error> {
+        Err(unsupported("bool"))
+    }
+    fn serialize_i8(self, _v: i8) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_i16(self, _v: i16) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_i32(self, _v: i32) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_i64(self, _v: i64) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u16(self, _v: u16) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u32(self, _v: u32) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_u64(self, _v: u64) -> Result<u8, Error> {
+        Err(unsupported("integer"))
+    }
+    fn serialize_f32(self, _v: f32) -> Result<u8, Error> {
+        Err(unsupported("float"))
+    }
+    fn serialize_f64(self, _v: f64) -> Result<u8, Error> {
+        Err(unsupported("float"))
+    }
+    fn serialize_char(self, _v: char) -> Result<u8, Error> {
+        Err(unsupported("char"))
+    }
+    fn serialize_str(self, _v: &str) -> Result<u8, Error> {
+        Err(unsupported("nested string"))
+    }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<u8, Error> {
+        Err(unsupported("nested sequence"))
+    }
+    fn serialize_none(self) -> Result<u8, Error> {
+        Err(unsupported("option"))
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<u8, Error> {
+        Err(unsupported("option"))
+    }
+    fn serialize_unit(self) -> Result<u8, Error> {
+        Err(unsupported("nested unit"))
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<u8, Error> {
+        Err(unsupported("nested unit"))
+    }
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str) -> Result<u8, Error> {
+        Err(unsupported("enum"))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<u8, Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<u8, Error> {
+        Err(unsupported("enum"))
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported("nested sequence"))
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("nested sequence"))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("struct"))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("enum"))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("map"))
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported("struct"))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("enum"))
+    }
+}
+
+struct RawDeserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> de::Deserializer<'de> for RawDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.input)
+    }
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let text = std::str::from_utf8(self.input).map_err(|_| Error::InvalidString)?;
+
+        visitor.visit_borrowed_str(text)
+    }
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::<_, Error>::new(self.input.iter().copied()))
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_bool<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("bool"))
+    }
+    fn deserialize_i8<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_i16<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_i32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_i64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_u8<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_u16<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_u32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_u64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("integer"))
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("float"))
+    }
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("float"))
+    }
+    fn deserialize_char<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("char"))
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("option"))
+    }
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("tuple"))
+    }
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("struct"))
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(unsupported("map"))
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(unsupported("struct"))
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(unsupported("enum"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf identifier ignored_any
+    }
+}
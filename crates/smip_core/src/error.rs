@@ -1,11 +1,82 @@
+use serde::de::DeserializeOwned;
+use someip_types::{InstanceId, MessageType, ReturnCode, ServiceId};
 use thiserror::Error;
 
+use crate::{codec::PayloadCodec, types::*};
+
 #[derive(Error, Debug)]
 pub enum SmipError {
     #[error("Failed to parse payload {0}")]
-    FromPayloadError(bincode::Error),
+    FromPayloadError(Box<dyn std::error::Error + Send + Sync>),
     #[error("Failed to write payload {0}")]
-    ToPayloadError(bincode::Error),
-    #[error("No response received")]
-    NoResponse
+    ToPayloadError(Box<dyn std::error::Error + Send + Sync>),
+    #[error("No response received in time")]
+    Timeout,
+    #[error("Service is not available")]
+    ServiceUnavailable,
+    #[error("Request could not be delivered {0:?}")]
+    Transport(ReturnCode),
+    #[error("Service responded with error {code:?}")]
+    Remote {
+        code: ReturnCode,
+        /// Error payload sent by the service, encoded with its codec.
+        payload: Vec<u8>,
+    },
+    #[error("Method panicked: {0}")]
+    Panicked(String),
+    #[error("Service {0:#06x} instance {1:#06x} is not running")]
+    ServiceNotRunning(ServiceId, InstanceId),
+    #[error("Service {0:#06x} instance {1:#06x} is already running")]
+    ServiceAlreadyRunning(ServiceId, InstanceId),
+    #[error("Service {0:#06x} instance {1:#06x} was not reserved before the runtime started")]
+    ServiceNotReserved(ServiceId, InstanceId),
+    #[error("Event is not offered yet")]
+    EventNotOffered,
+    #[error("Blocking call made from within an executor, use the async variant instead")]
+    BlockingInAsync,
+}
+
+impl SmipError {
+    /// Whether repeating the request might succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, SmipError::Timeout | SmipError::ServiceUnavailable | SmipError::Transport(_))
+    }
+    /// Decodes the payload of a [`SmipError::Remote`] error, `C` has to be the codec of the service.
+    pub fn remote_payload<C: PayloadCodec, T: DeserializeOwned>(&self) -> Option<Result<T, SmipError>> {
+        match self {
+            SmipError::Remote { payload, .. } => Some(C::decode(payload)),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by a service method, sent to the caller as a response with
+/// message type `Error`.
+///
+/// Implemented for every type that converts into a [`ReturnCode`], implement
+/// it directly to also send an error payload.
+pub trait SmipMethodError {
+    fn return_code(&self) -> ReturnCode;
+    fn error_payload<C: PayloadCodec>(&self) -> Result<Option<Vec<u8>>, SmipError> {
+        Ok(None)
+    }
+}
+
+impl<E: Into<ReturnCode> + Clone> SmipMethodError for E {
+    fn return_code(&self) -> ReturnCode {
+        self.clone().into()
+    }
+}
+
+/// Builds the error response to `request`, used by the code generated for methods returning a `Result`.
+pub fn error_response<C: PayloadCodec, E: SmipMethodError>(request: &Message, error: &E) -> Result<Message, SmipError> {
+    let mut response = Message::response(request);
+    response.set_message_type(MessageType::Error);
+    response.set_return_code(error.return_code());
+
+    if let Some(payload) = error.error_payload::<C>()? {
+        response.set_payload(&Payload::with_data(&payload));
+    }
+
+    Ok(response)
 }
\ No newline at end of file
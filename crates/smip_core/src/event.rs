@@ -0,0 +1,89 @@
+use std::{marker::PhantomData, sync::{Arc, OnceLock}};
+
+use someip_types::*;
+
+use serde::Serialize;
+
+use crate::{codec::PayloadCodec, error::SmipError, types::*};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventDefinition {
+    pub id: EventId,
+    pub eventgroup: EventGroupId,
+    pub event_type: EventType,
+}
+
+/// Passed to [`ServiceDefinition::bind_events`] once the service is offered,
+/// gives the service's events access to the application that publishes them.
+pub struct EventBinder<S> {
+    pub(crate) application: Application,
+    pub(crate) service_id: ServiceId,
+    pub(crate) instance_id: InstanceId,
+    pub(crate) _service: PhantomData<fn() -> S>,
+}
+
+struct EventBinding<T> {
+    application: Application,
+    service_id: ServiceId,
+    instance_id: InstanceId,
+    event_id: EventId,
+    encode: fn(&T) -> Result<Vec<u8>, SmipError>,
+}
+
+/// Handle used by a service to publish notifications for one of its events.
+///
+/// Clones share the same binding, so a clone can be moved to another thread
+/// before the service is handed to the [`Runtime`](crate::Runtime).
+pub struct Event<T> {
+    binding: Arc<OnceLock<EventBinding<T>>>,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T> Event<T> {
+    pub fn new() -> Self {
+        Self {
+            binding: Arc::new(OnceLock::new()),
+            _marker: PhantomData,
+        }
+    }
+    pub fn is_offered(&self) -> bool {
+        self.binding.get().is_some()
+    }
+    pub fn notify(&self, value: &T) -> Result<(), SmipError> {
+        let binding = self.binding.get().ok_or(SmipError::EventNotOffered)?;
+        let _span = tracing::debug_span!("smip.notify", service = binding.service_id, instance = binding.instance_id, event = binding.event_id).entered();
+        let payload = Payload::with_data(&(binding.encode)(value)?);
+
+        binding.application.notify(binding.service_id, binding.instance_id, binding.event_id, &payload, false);
+
+        Ok(())
+    }
+}
+
+impl<T: Serialize> Event<T> {
+    /// Notifications are encoded with the codec of the service `S` the event belongs to.
+    pub fn bind<S: ServiceDefinition>(&self, binder: &EventBinder<S>, id: EventId) {
+        let _ = self.binding.set(EventBinding {
+            application: binder.application.clone(),
+            service_id: binder.service_id,
+            instance_id: binder.instance_id,
+            event_id: id,
+            encode: |value| S::Codec::encode(value),
+        });
+    }
+}
+
+impl<T> Clone for Event<T> {
+    fn clone(&self) -> Self {
+        Self {
+            binding: self.binding.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Event<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,15 @@
+use futures::{executor::ThreadPool, future::BoxFuture};
+
+/// Drives the futures of `async` service methods.
+///
+/// The [`Runtime`](crate::Runtime) uses a [`ThreadPool`] unless another
+/// executor is set with [`Runtime::executor`](crate::Runtime::executor).
+pub trait Executor: Send + Sync + 'static {
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+}
+
+impl Executor for ThreadPool {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        self.spawn_ok(future);
+    }
+}
@@ -0,0 +1,73 @@
+use std::ops::Deref;
+
+use someip_types::*;
+
+use serde::Serialize;
+
+use crate::{error::SmipError, event::*, types::*};
+
+/// Value of a SOME/IP field, the generated getter and setter methods read and
+/// write it while the notifier event publishes every change.
+pub struct Field<T> {
+    value: T,
+    notifier: Event<T>,
+}
+
+impl<T> Field<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            notifier: Event::new(),
+        }
+    }
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+    pub fn notifier(&self) -> &Event<T> {
+        &self.notifier
+    }
+}
+
+impl<T: Serialize> Field<T> {
+    pub fn bind<S: ServiceDefinition>(&self, binder: &EventBinder<S>, id: EventId) {
+        self.notifier.bind(binder, id);
+
+        // Subscribers of a field receive its current value right after subscribing
+        let _ = self.notifier.notify(&self.value);
+    }
+}
+
+impl<T: PartialEq> Field<T> {
+    /// Updates the value and notifies subscribers if it changed, returns whether it did.
+    ///
+    /// The value is updated before subscribers are notified, so an error means the
+    /// value changed but the notification couldn't be sent. The generated setter
+    /// method then answers with an error although the new value is kept.
+    pub fn set(&mut self, value: T) -> Result<bool, SmipError> {
+        if self.value == value {
+            return Ok(false);
+        }
+
+        self.value = value;
+
+        if self.notifier.is_offered() {
+            self.notifier.notify(&self.value)?;
+        }
+
+        Ok(true)
+    }
+}
+
+impl<T> Deref for Field<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T: Default> Default for Field<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
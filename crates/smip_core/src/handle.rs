@@ -0,0 +1,254 @@
+use std::{collections::HashSet, sync::{Arc, Weak}, thread::JoinHandle, time::{Duration, Instant}};
+
+use parking_lot::{Condvar, Mutex};
+use someip_types::*;
+
+use crate::{error::SmipError, executor::Executor, panic::PanicPolicy, types::*, Runtime};
+
+/// Everything services need from the runtime they run in.
+pub(crate) struct RuntimeContext {
+    pub application: Application,
+    pub instance_id: InstanceId,
+    pub executor: Arc<dyn Executor>,
+    pub in_flight: Arc<InFlight>,
+    pub panic_policy: PanicPolicy,
+    /// Services configured in the application, only these can be added while it runs.
+    pub reserved: HashSet<(ServiceId, InstanceId)>,
+    /// Services running in the application, weak since they refer back to it.
+    pub services: Weak<Mutex<Services>>,
+}
+
+/// Controls of a service started by the [`Runtime`].
+pub(crate) struct RunningService {
+    pub service_id: ServiceId,
+    pub instance_id: InstanceId,
+    /// Paused services aren't offered until they're resumed.
+    pub paused: bool,
+    /// Offers the service and its events, the first offer runs the `on_start` hook.
+    pub offer: Box<dyn Fn() + Send>,
+    /// Withdraws the offer of the service and its events.
+    pub stop_offer: Box<dyn Fn() + Send>,
+    /// Unregisters the handlers of the service and runs its `on_stop` hook, once
+    /// the running handlers are done or the deadline passed.
+    pub stop: Box<dyn FnOnce(Instant) + Send>,
+}
+
+#[derive(Default)]
+pub(crate) struct Services {
+    /// Whether the application is registered at the routing manager, services can only be offered while it is.
+    pub registered: bool,
+    pub running: Vec<RunningService>,
+}
+
+impl Services {
+    fn position(&self, service_id: ServiceId, instance_id: InstanceId) -> Result<usize, SmipError> {
+        self.running.iter()
+            .position(|service| service.service_id == service_id && service.instance_id == instance_id)
+            .ok_or(SmipError::ServiceNotRunning(service_id, instance_id))
+    }
+    fn get_mut(&mut self, service_id: ServiceId, instance_id: InstanceId) -> Result<&mut RunningService, SmipError> {
+        let ix = self.position(service_id, instance_id)?;
+        Ok(&mut self.running[ix])
+    }
+    pub fn pause(&mut self, service_id: ServiceId, instance_id: InstanceId) -> Result<(), SmipError> {
+        let registered = self.registered;
+        let service = self.get_mut(service_id, instance_id)?;
+
+        if !service.paused && registered {
+            (service.stop_offer)();
+        }
+        service.paused = true;
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct InFlightState {
+    count: usize,
+    closed: bool,
+}
+
+/// Counts the requests that are being handled, so shutdown can wait for them.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    state: Mutex<InFlightState>,
+    drained: Condvar,
+}
+
+impl InFlight {
+    /// Registers a new request, fails once the runtime is shutting down.
+    pub fn enter(self: &Arc<Self>) -> Option<InFlightGuard> {
+        let mut state = self.state.lock();
+        if state.closed {
+            return None;
+        }
+
+        state.count += 1;
+        Some(InFlightGuard(self.clone()))
+    }
+    pub fn close(&self) {
+        self.state.lock().closed = true;
+    }
+    /// Waits until all requests completed, returns false if the timeout elapsed first.
+    pub fn wait_drained(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock();
+
+        while state.count > 0 {
+            if self.drained.wait_until(&mut state, deadline).timed_out() {
+                return state.count == 0;
+            }
+        }
+
+        true
+    }
+}
+
+pub(crate) struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock();
+        state.count -= 1;
+
+        if state.count == 0 {
+            self.0.drained.notify_all();
+        }
+    }
+}
+
+/// Handle to a started [`Runtime`], returned by [`Runtime::start`].
+pub struct RuntimeHandle {
+    pub(crate) context: RuntimeContext,
+    pub(crate) services: Arc<Mutex<Services>>,
+    pub(crate) shutdown_timeout: Duration,
+    pub(crate) thread: JoinHandle<()>,
+}
+
+impl RuntimeHandle {
+    /// Adds a service to the running application and offers it, as the instance set in the [`RuntimeConfig`](crate::RuntimeConfig).
+    ///
+    /// Its port has to be configured up front with [`Runtime::reserve_service`].
+    pub fn add_service<S: ServiceDefinition + ServiceMethods>(&self, service: S) -> Result<(), SmipError> {
+        self.add_service_instance(service, self.context.instance_id)
+    }
+    /// Adds a service instance to the running application and offers it.
+    ///
+    /// Its port has to be configured up front with [`Runtime::reserve_service_instance`].
+    pub fn add_service_instance<S: ServiceDefinition + ServiceMethods>(&self, service: S, instance_id: InstanceId) -> Result<(), SmipError> {
+        if !self.context.reserved.contains(&(S::id(), instance_id)) {
+            return Err(SmipError::ServiceNotReserved(S::id(), instance_id));
+        }
+
+        let mut services = self.services.lock();
+        if services.position(S::id(), instance_id).is_ok() {
+            return Err(SmipError::ServiceAlreadyRunning(S::id(), instance_id));
+        }
+
+        let service = Runtime::create_service(&self.context, service, instance_id);
+        if services.registered {
+            (service.offer)();
+        }
+
+        services.running.push(service);
+
+        Ok(())
+    }
+    /// Stops offering a service and removes it, running its `on_stop` hook.
+    pub fn remove_service(&self, service_id: ServiceId, instance_id: InstanceId) -> Result<(), SmipError> {
+        let service = {
+            let mut services = self.services.lock();
+            let ix = services.position(service_id, instance_id)?;
+
+            services.running.remove(ix)
+        };
+
+        if !service.paused {
+            (service.stop_offer)();
+        }
+        (service.stop)(Instant::now() + self.shutdown_timeout);
+
+        Ok(())
+    }
+    /// Stops offering a service while keeping its state, until it's resumed.
+    pub fn pause(&self, service_id: ServiceId, instance_id: InstanceId) -> Result<(), SmipError> {
+        self.services.lock().pause(service_id, instance_id)
+    }
+    /// Offers a paused service again.
+    pub fn resume(&self, service_id: ServiceId, instance_id: InstanceId) -> Result<(), SmipError> {
+        let mut services = self.services.lock();
+        let registered = services.registered;
+        let service = services.get_mut(service_id, instance_id)?;
+
+        if service.paused && registered {
+            (service.offer)();
+        }
+        service.paused = false;
+
+        Ok(())
+    }
+    /// Stops offering all services, waits for the requests that are being
+    /// handled and stops the application.
+    ///
+    /// Requests arriving in the meantime are answered with `ReturnCode::NotReady`.
+    pub fn shutdown(self) {
+        let deadline = Instant::now() + self.shutdown_timeout;
+        self.context.in_flight.close();
+
+        let services = std::mem::take(&mut self.services.lock().running);
+
+        for service in services.iter().filter(|service| !service.paused) {
+            (service.stop_offer)();
+        }
+
+        if !self.context.in_flight.wait_drained(self.shutdown_timeout) {
+            tracing::warn!("Shutting down with requests still in flight");
+        }
+
+        for service in services {
+            (service.stop)(deadline);
+        }
+
+        self.context.application.stop();
+        let _ = self.thread.join();
+    }
+    /// Blocks until the application stops.
+    pub fn wait(self) {
+        let _ = self.thread.join();
+    }
+    /// Blocks until the process receives SIGINT or SIGTERM and shuts down.
+    #[cfg(feature = "signals")]
+    pub fn shutdown_on_signal(self) -> std::io::Result<()> {
+        crate::signal::wait_for_signal()?;
+        self.shutdown();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_in_flight() {
+        let in_flight = Arc::new(InFlight::default());
+
+        let guard = in_flight.enter().unwrap();
+        assert!(!in_flight.wait_drained(Duration::from_millis(10)));
+
+        in_flight.close();
+        assert!(in_flight.enter().is_none());
+
+        let waiter = {
+            let in_flight = in_flight.clone();
+            thread::spawn(move || in_flight.wait_drained(Duration::from_secs(5)))
+        };
+
+        drop(guard);
+        assert!(waiter.join().unwrap());
+    }
+}
@@ -2,10 +2,47 @@ mod runtime;
 mod types;
 mod client;
 mod error;
+mod event;
+mod field;
+mod subscription;
+mod codec;
+mod pending;
+mod options;
+mod executor;
+mod panic;
+mod handle;
+mod trace;
+mod telemetry;
+#[cfg(feature = "signals")]
+mod signal;
+pub mod serializer;
 
 pub use runtime::*;
 pub use types::*;
 pub use client::*;
 pub use error::*;
+pub use event::*;
+pub use field::*;
+pub use subscription::*;
+pub use codec::*;
+pub use options::*;
+pub use executor::*;
+pub use panic::PanicPolicy;
+pub use handle::RuntimeHandle;
+pub use smip_al::{ApplicationConfig, Backend, BackendError, Loopback, Message, Noop, Payload, ServiceConfig, PROTOCOL_VERSION};
+#[cfg(feature = "vsomeip")]
+pub use smip_al::VsomeIp;
+#[cfg(feature = "prometheus")]
+pub use telemetry::MetricsExporter;
+
+/// Used by the code the macros generate, not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::trace::record_elapsed;
+
+    /// Implemented by the macros for every method id of a service, an id used
+    /// twice shows up as conflicting implementations.
+    pub trait MethodIdUsed<const ID: u16> {}
+}
 
 
@@ -0,0 +1,105 @@
+use std::time::{Duration, Instant};
+
+use crate::error::SmipError;
+
+/// Per call settings for [`Client`](crate::Client) requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallOptions {
+    /// How long to wait for the response of a single attempt.
+    pub timeout: Duration,
+    /// Number of times a request is repeated after a timeout or transport error.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every following one.
+    pub backoff: Duration,
+    /// Send the request over TCP instead of UDP.
+    pub reliable: bool,
+    /// Point in time after which no further attempts are made, regardless of
+    /// the remaining retries.
+    pub deadline: Option<Instant>,
+}
+
+impl CallOptions {
+    pub const fn new() -> Self {
+        Self {
+            timeout: Duration::from_secs(3),
+            retries: 0,
+            backoff: Duration::from_millis(100),
+            reliable: true,
+            deadline: None,
+        }
+    }
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    pub fn reliable(mut self, reliable: bool) -> Self {
+        self.reliable = reliable;
+        self
+    }
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Timeout of the next attempt, limited by the deadline.
+    pub(crate) fn attempt_timeout(&self) -> Result<Duration, SmipError> {
+        match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(SmipError::Timeout);
+                }
+
+                Ok(remaining.min(self.timeout))
+            },
+            None => Ok(self.timeout),
+        }
+    }
+    /// Delay before the next retry, which never waits past the deadline.
+    pub(crate) fn retry_delay(&self, backoff: Duration) -> Duration {
+        match self.deadline {
+            Some(deadline) => backoff.min(deadline.saturating_duration_since(Instant::now())),
+            None => backoff,
+        }
+    }
+}
+
+impl Default for CallOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attempt_timeout() {
+        let options = CallOptions::new().timeout(Duration::from_secs(1));
+        assert_eq!(options.attempt_timeout().unwrap(), Duration::from_secs(1));
+
+        let options = options.deadline(Instant::now() + Duration::from_millis(100));
+        assert!(options.attempt_timeout().unwrap() <= Duration::from_millis(100));
+
+        let options = options.deadline(Instant::now());
+        assert!(matches!(options.attempt_timeout(), Err(SmipError::Timeout)));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let options = CallOptions::new();
+        assert_eq!(options.retry_delay(Duration::from_secs(10)), Duration::from_secs(10));
+
+        let options = options.deadline(Instant::now() + Duration::from_millis(100));
+        assert!(options.retry_delay(Duration::from_secs(10)) <= Duration::from_millis(100));
+    }
+}
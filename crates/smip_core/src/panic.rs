@@ -0,0 +1,95 @@
+use std::{any::Any, sync::{atomic::{AtomicBool, Ordering}, Arc, Weak}};
+
+use parking_lot::Mutex;
+use someip_types::*;
+
+use crate::{executor::Executor, handle::Services};
+
+/// What the [`Runtime`](crate::Runtime) does with a service after one of its
+/// methods panicked.
+///
+/// The caller of the method always receives an error response with
+/// `ReturnCode::NotOk`, the policy decides whether the possibly inconsistent
+/// service keeps serving requests afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    /// Keep offering the service.
+    #[default]
+    Continue,
+    /// Stop offering the service, already subscribed clients see it as unavailable.
+    ///
+    /// The service is paused like with [`RuntimeHandle::pause`](crate::RuntimeHandle::pause),
+    /// so [`RuntimeHandle::resume`](crate::RuntimeHandle::resume) offers it again.
+    StopOffering,
+    /// Exit the process so it can be restarted in a clean state.
+    Exit,
+}
+
+/// Marks a service as degraded once one of its methods panicked.
+pub(crate) struct ServiceHealth {
+    pub service_id: ServiceId,
+    pub instance_id: InstanceId,
+    pub policy: PanicPolicy,
+    /// Services of the runtime, to pause the degraded one.
+    pub services: Weak<Mutex<Services>>,
+    pub executor: Arc<dyn Executor>,
+    pub degraded: AtomicBool,
+}
+
+impl ServiceHealth {
+    pub fn degrade(&self) {
+        if self.degraded.swap(true, Ordering::SeqCst) && self.policy != PanicPolicy::Exit {
+            return;
+        }
+
+        match self.policy {
+            PanicPolicy::Continue => {
+                tracing::warn!("Service {:#06x} instance {:#06x} is degraded", self.service_id, self.instance_id);
+            },
+            PanicPolicy::StopOffering => {
+                tracing::warn!("Service {:#06x} instance {:#06x} is degraded, no longer offering it", self.service_id, self.instance_id);
+
+                // Degrading can happen while the services are locked, like in the `on_start` hook
+                let (services, service_id, instance_id) = (self.services.clone(), self.service_id, self.instance_id);
+                self.executor.spawn(Box::pin(async move {
+                    if let Some(services) = services.upgrade() {
+                        // The service may have been removed in the meantime
+                        let _ = services.lock().pause(service_id, instance_id);
+                    }
+                }));
+            },
+            PanicPolicy::Exit => {
+                tracing::error!("Service {:#06x} instance {:#06x} is degraded, exiting", self.service_id, self.instance_id);
+                std::process::exit(1);
+            },
+        }
+    }
+}
+
+/// Message passed to `panic!`, if it was a string.
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        let panic = std::panic::catch_unwind(|| panic!("oops")).unwrap_err();
+        assert_eq!(panic_message(&*panic), "oops");
+
+        let panic = std::panic::catch_unwind(|| panic!("value {}", 1)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "value 1");
+
+        let panic = std::panic::catch_unwind(|| std::panic::panic_any(1u32)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "Box<dyn Any>");
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+use someip_types::*;
+use tracing::Span;
+
+use crate::{error::SmipError, types::*};
+
+struct Pending {
+    token: u64,
+    sender: oneshot::Sender<Result<Message, SmipError>>,
+}
+
+#[derive(Default)]
+struct State {
+    available: bool,
+    next_token: u64,
+    /// Requests waiting for the service, with the span of the call that sent them.
+    queued: Vec<(Message, Option<Pending>, Span)>,
+    in_flight: HashMap<RequestId, Pending>,
+}
+
+/// Requests of a client waiting for their response, correlated by request id
+/// (client id and session id) so concurrent calls to the same method can't
+/// receive each other's responses.
+pub(crate) struct PendingRequests {
+    application: Application,
+    state: Mutex<State>,
+}
+
+impl PendingRequests {
+    pub fn new(application: Application) -> Self {
+        Self {
+            application,
+            state: Mutex::new(State::default()),
+        }
+    }
+    /// Sends the request, or queues it until the service becomes available.
+    /// The returned token identifies the request for [`Self::cancel`].
+    pub fn send(&self, message: Message) -> (u64, oneshot::Receiver<Result<Message, SmipError>>) {
+        let (sender, receiver) = oneshot::channel();
+
+        let mut state = self.state.lock();
+        let token = state.next_token;
+        state.next_token += 1;
+
+        let pending = Pending { token, sender };
+        if state.available {
+            self.dispatch(&mut state, message, Some(pending), &Span::current());
+        } else {
+            state.queued.push((message, Some(pending), Span::current()));
+        }
+
+        (token, receiver)
+    }
+    /// Sends a request that doesn't expect a response, queueing it like any other request.
+    pub fn send_no_return(&self, message: Message) {
+        let mut state = self.state.lock();
+
+        if state.available {
+            self.dispatch(&mut state, message, None, &Span::current());
+        } else {
+            state.queued.push((message, None, Span::current()));
+        }
+    }
+    fn dispatch(&self, state: &mut State, mut message: Message, pending: Option<Pending>, span: &Span) {
+        // The transport assigns the session id while sending, holding the lock keeps
+        // the response from being handled before the request is registered
+        self.application.send(&mut message);
+
+        span.record("client", message.get_client());
+        span.record("session", message.get_session());
+
+        if let Some(pending) = pending {
+            state.in_flight.insert(message.get_request(), pending);
+        }
+    }
+    pub fn set_available(&self, available: bool) {
+        let mut state = self.state.lock();
+        state.available = available;
+
+        if available {
+            for (message, pending, span) in std::mem::take(&mut state.queued) {
+                self.dispatch(&mut state, message, pending, &span);
+            }
+        } else {
+            // Responses to requests sent before the service went away won't arrive anymore
+            for (_, pending) in state.in_flight.drain() {
+                let _ = pending.sender.send(Err(SmipError::ServiceUnavailable));
+            }
+        }
+    }
+    pub fn complete(&self, response: &Message) {
+        let pending = self.state.lock().in_flight.remove(&response.get_request());
+
+        if let Some(pending) = pending {
+            let _ = pending.sender.send(Ok(response.clone()));
+        }
+    }
+    pub fn is_queued(&self, token: u64) -> bool {
+        self.state.lock().queued.iter().any(|(_, pending, _)| pending.as_ref().is_some_and(|pending| pending.token == token))
+    }
+    /// Forgets a request that timed out or whose caller stopped waiting, a
+    /// response arriving later is dropped.
+    pub fn cancel(&self, token: u64) {
+        let mut state = self.state.lock();
+
+        state.queued.retain(|(_, pending, _)| pending.as_ref().is_none_or(|pending| pending.token != token));
+        state.in_flight.retain(|_, pending| pending.token != token);
+    }
+}
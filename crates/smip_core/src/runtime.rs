@@ -1,20 +1,39 @@
 use crate::*;
-use std::{net::{IpAddr, ToSocketAddrs}, sync::Arc};
+#[cfg(feature = "vsomeip")]
+use std::net::{IpAddr, ToSocketAddrs};
+use std::{marker::PhantomData, panic::{self, AssertUnwindSafe}, sync::{atomic::{AtomicBool, Ordering}, Arc, Weak}, thread, time::{Duration, Instant}};
 
+use futures::{executor::ThreadPool, future::{self, Either}, FutureExt};
+use futures_timer::Delay;
 use parking_lot::Mutex;
-use someip_types::InstanceId;
-use vsomeip_rs::{ReturnCode, State, VSomeIpError};
+use tracing::{Instrument, Span};
+use smip_al::{ApplicationConfig, ServiceConfig};
+use someip_types::{ConnectionType, InstanceId, MessageType, ReturnCode, ServiceId, State};
+use tokio::sync::RwLock;
+#[cfg(feature = "vsomeip")]
 use vsomeip_compat::*;
 
 use self::error::SmipError;
+use crate::{handle::{InFlight, RunningService, RuntimeContext, Services}, panic::{panic_message, ServiceHealth}, telemetry, trace};
 
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     name: String,
     id: u16,
+    #[cfg(feature = "vsomeip")]
     addr: Option<IpAddr>,
+    #[cfg(feature = "vsomeip")]
     netmask: Option<IpAddr>,
     instance_id: InstanceId,
+    #[cfg(feature = "vsomeip")]
+    service_discovery: Option<ServiceDiscoveryConfig>,
+    #[cfg(feature = "vsomeip")]
+    logging: Option<LoggingConfig>,
+    #[cfg(feature = "vsomeip")]
+    base_config: Option<VsomeIpConfig>,
+    panic_policy: PanicPolicy,
+    shutdown_timeout: Duration,
+    backend: Option<Arc<dyn Backend>>,
 }
 
 impl RuntimeConfig {
@@ -22,70 +41,270 @@ impl RuntimeConfig {
         Self {
             name: name.as_ref().to_owned(),
             id,
+            #[cfg(feature = "vsomeip")]
             addr: None,
+            #[cfg(feature = "vsomeip")]
             netmask: None,
             instance_id,
+            #[cfg(feature = "vsomeip")]
+            service_discovery: None,
+            #[cfg(feature = "vsomeip")]
+            logging: None,
+            #[cfg(feature = "vsomeip")]
+            base_config: None,
+            panic_policy: PanicPolicy::default(),
+            shutdown_timeout: Duration::from_secs(5),
+            backend: None,
         }
     }
+    #[cfg(feature = "vsomeip")]
     pub fn addr(mut self, addr: impl ToSocketAddrs) -> Self {
         self.addr = Some(addr.to_socket_addrs().expect("Invalid address").next().unwrap().ip());
         self
     }
+    #[cfg(feature = "vsomeip")]
     pub fn netmask(mut self, netmask: impl ToSocketAddrs) -> Self {
         self.netmask = Some(netmask.to_socket_addrs().expect("Invalid address").next().unwrap().ip());
         self
     }
+    /// Sets how the services are announced and found, service discovery is disabled by default.
+    #[cfg(feature = "vsomeip")]
+    pub fn service_discovery(mut self, service_discovery: ServiceDiscoveryConfig) -> Self {
+        self.service_discovery = Some(service_discovery);
+        self
+    }
+    /// Sets where and how much vsomeip logs, see [`LoggingConfig::forward_to_log`] to include it in the log of the application.
+    #[cfg(feature = "vsomeip")]
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.logging = Some(logging);
+        self
+    }
+    /// Sets a vsomeip configuration, usually loaded with [`VsomeIpConfig::from_file`], that the
+    /// generated configuration is merged into.
+    ///
+    /// Its application, instance id and routing are replaced by the ones of this config, the
+    /// endpoints of services added to the [`Runtime`] replace the ones it sets for them.
+    #[cfg(feature = "vsomeip")]
+    pub fn base_config(mut self, base_config: VsomeIpConfig) -> Self {
+        self.base_config = Some(base_config);
+        self
+    }
+    /// Sets what happens to a service after one of its methods panicked.
+    pub fn panic_policy(mut self, panic_policy: PanicPolicy) -> Self {
+        self.panic_policy = panic_policy;
+        self
+    }
+    /// Sets how long [`RuntimeHandle::shutdown`] waits for requests that are still being handled.
+    ///
+    /// It also bounds how long removing a service waits for its methods before
+    /// running `on_stop`, the hook is skipped when they don't finish in time.
+    pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+    /// Sets the SOME/IP implementation the services run on, `VsomeIp` with the
+    /// vsomeip settings of this config by default.
+    ///
+    /// Any [`Backend`] can be used, the runtime keeps it as an `Arc<dyn Backend>`.
+    /// With another backend, like the in-process [`Loopback`] whose services only
+    /// clients of the same loopback can reach, the vsomeip settings are ignored.
+    pub fn backend(mut self, backend: impl Backend) -> Self {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+    /// The backend that was set, or vsomeip configured by this config.
+    fn create_backend(&self) -> Result<Arc<dyn Backend>, BackendError> {
+        if let Some(backend) = &self.backend {
+            return Ok(backend.clone());
+        }
+
+        #[cfg(feature = "vsomeip")]
+        {
+            let mut vsomeip_config = self.base_config.clone().unwrap_or_default();
+
+            vsomeip_config.instance_id = self.instance_id;
+            vsomeip_config.routing = Some(self.name.clone());
+
+            if self.addr.is_some() {
+                vsomeip_config.addr = self.addr;
+            }
+            if self.netmask.is_some() {
+                vsomeip_config.netmask = self.netmask;
+            }
+            if let Some(service_discovery) = &self.service_discovery {
+                vsomeip_config.service_discovery = service_discovery.clone();
+            }
+            if let Some(logging) = &self.logging {
+                vsomeip_config.logging = logging.clone();
+            }
+
+            Ok(Arc::new(VsomeIp::new(vsomeip_config)))
+        }
+
+        #[cfg(not(feature = "vsomeip"))]
+        Err(BackendError {
+            name: self.name.clone(),
+            source: "no backend is set and the vsomeip feature is disabled".into(),
+        })
+    }
+}
+
+type ServiceCreator = Box<dyn FnOnce(&RuntimeContext) -> RunningService>;
+
+/// How the handlers of a service reach it, see [`Concurrency`].
+enum SharedService<S> {
+    Locked(Arc<RwLock<S>>),
+    /// Handlers count themselves in the service's own requests in flight while
+    /// they hold it, so `on_stop` can take the service back once they're done.
+    Unsynchronized(Weak<S>, Arc<InFlight>),
+}
+
+impl<S> Clone for SharedService<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Locked(service) => Self::Locked(service.clone()),
+            Self::Unsynchronized(service, in_flight) => Self::Unsynchronized(service.clone(), in_flight.clone()),
+        }
+    }
+}
+
+/// The service kept until it's stopped.
+enum OwnedService<S> {
+    Locked(Arc<RwLock<S>>),
+    Unsynchronized(Arc<S>, Arc<InFlight>),
 }
 
 pub struct Runtime {
     config: RuntimeConfig,
-    vsomeip_config: VsomeIpConfig,
-    service_creators: Vec<Box<dyn FnOnce(&vsomeip_rs::Application, InstanceId)>>,
+    services: Vec<ServiceConfig>,
+    service_creators: Vec<ServiceCreator>,
+    executor: Option<Arc<dyn Executor>>,
 }
 
 impl Runtime {
     pub fn new(config: RuntimeConfig) -> Self {
-        let mut vsomeip_config = VsomeIpConfig::new();
-        
-        vsomeip_config.app_id = (config.name.clone(), config.id);
-        vsomeip_config.instance_id = config.instance_id;
-        vsomeip_config.routing = Some(config.name.clone());
-
         Self {
             config,
+            services: vec![],
             service_creators: vec![],
-            vsomeip_config,
+            executor: None,
         }
     }
-    fn handle_response(application: &Application, request: &Message, result: Result<Option<Message>, SmipError>) {
-        match result {
-            Ok(response) => {
-                response.map(|response| application.send(&response));
-            },
+    /// Sets the executor running `async` methods, a [`ThreadPool`] is created if none is set.
+    pub fn executor(mut self, executor: impl Executor) -> Self {
+        self.executor = Some(Arc::new(executor));
+        self
+    }
+    fn handle_response(application: &Application, request: &Message, result: Result<Option<Message>, SmipError>, started: Instant) {
+        let response = match result {
+            Ok(response) => response,
             Err(err) => {
-                match err {
-                    SmipError::FromPayloadError(bincode_err) => {
-                        let mut response = Message::response(request);
-                        response.set_return_code(ReturnCode::MalformedMessage);
-                        
-                        println!("{}", bincode_err);
-
-                        application.send(&response);
-                    },
-                    SmipError::ToPayloadError(bincode_err) => {
-                        let mut response = Message::response(request);
-                        response.set_return_code(ReturnCode::NotOk);
-                        
-                        println!("{}", bincode_err);
+                let return_code = match err {
+                    SmipError::FromPayloadError(_) => ReturnCode::MalformedMessage,
+                    SmipError::ServiceUnavailable => ReturnCode::NotReady,
+                    _ => ReturnCode::NotOk,
+                };
 
-                        application.send(&response);
-                    },
-                    SmipError::NoResponse => unreachable!(),
-                }
+                tracing::warn!("Request to service {:#06x} method {:#06x} failed: {}", request.get_service(), request.get_method(), err);
+
+                let mut response = Message::response(request);
+                response.set_message_type(MessageType::Error);
+                response.set_return_code(return_code);
+
+                Some(response)
             }
+        };
+
+        telemetry::request_completed(request, response.as_ref(), started);
+
+        // The sender of a fire and forget request doesn't expect any answer, not even an error
+        if request.get_message_type() == MessageType::RequestNoReturn {
+            return;
+        }
+
+        if let Some(response) = response {
+            let mut response = response;
+            trace::record_return_code(&Span::current(), response.get_return_code());
+            application.send(&mut response);
         }
     }
-    pub fn service<S: ServiceDefinition + ServiceMethods>(mut self, service: S, port: u16) -> Self {
+    /// Sends the response of a handler, a panicking handler is answered with
+    /// an error and degrades its service.
+    fn complete(application: &Application, request: &Message, health: &ServiceHealth, result: thread::Result<Result<Option<Message>, SmipError>>, started: Instant) {
+        match result {
+            Ok(result) => Self::handle_response(application, request, result, started),
+            Err(panic) => {
+                Self::handle_response(application, request, Err(SmipError::Panicked(panic_message(&*panic).to_owned())), started);
+                health.degrade();
+            }
+        }
+    }
+    /// Runs a lifecycle hook of a service, returns false if it panicked.
+    fn event_binder<S>(application: &Application, service_id: ServiceId, instance_id: InstanceId) -> EventBinder<S> {
+        EventBinder {
+            application: application.clone(),
+            service_id,
+            instance_id,
+            _service: PhantomData,
+        }
+    }
+    /// Runs the `on_start` hook of a service, degrading it if the hook panics.
+    fn start_service<S: ServiceMethods>(service: &mut S, health: &ServiceHealth) {
+        if !Self::run_hook("on_start", health.service_id, health.instance_id, || service.on_start()) {
+            health.degrade();
+        }
+    }
+    fn run_hook(name: &str, service_id: ServiceId, instance_id: InstanceId, hook: impl FnOnce()) -> bool {
+        let result = panic::catch_unwind(AssertUnwindSafe(hook));
+        if let Err(panic) = &result {
+            tracing::error!("{} of service {:#06x} instance {:#06x} panicked: {}", name, service_id, instance_id, panic_message(&**panic));
+        }
+
+        result.is_ok()
+    }
+    /// Adds a service as the instance set in the [`RuntimeConfig`].
+    ///
+    /// `endpoint` is either a TCP port or a [`ConnectionType`] to offer the service over UDP or both.
+    pub fn service<S: ServiceDefinition + ServiceMethods>(self, service: S, endpoint: impl Into<ConnectionType>) -> Self {
+        let instance_id = self.config.instance_id;
+        self.service_instance(service, instance_id, endpoint)
+    }
+    /// Adds a service as the given instance, so several instances of the same
+    /// service can be offered on their own ports.
+    pub fn service_instance<S: ServiceDefinition + ServiceMethods>(mut self, service: S, instance_id: InstanceId, endpoint: impl Into<ConnectionType>) -> Self {
+        self = self.reserve_service_instance::<S>(instance_id, endpoint);
+
+        self.service_creators.push(Box::new(move |context| Self::create_service(context, service, instance_id)));
+
+        self
+    }
+    /// Configures the port of a service that is only added once the runtime
+    /// runs, with [`RuntimeHandle::add_service`].
+    pub fn reserve_service<S: ServiceDefinition>(self, endpoint: impl Into<ConnectionType>) -> Self {
+        let instance_id = self.config.instance_id;
+        self.reserve_service_instance::<S>(instance_id, endpoint)
+    }
+    /// Configures the port of a service instance that is only added once the
+    /// runtime runs, with [`RuntimeHandle::add_service_instance`].
+    pub fn reserve_service_instance<S: ServiceDefinition>(mut self, instance_id: InstanceId, endpoint: impl Into<ConnectionType>) -> Self {
+        let service_id = S::id();
+        assert!(
+            !self.services.iter().any(|service| service.id == service_id && service.instance_id == instance_id),
+            "service {:#06x} instance {:#06x} is added more than once", service_id, instance_id
+        );
+
+        self.services.push(ServiceConfig {
+            id: service_id,
+            instance_id,
+            major_version: S::major_version(),
+            minor_version: S::minor_version(),
+            conn_type: endpoint.into(),
+        });
+
+        self
+    }
+    /// Registers the handlers of a service, it's offered separately once the application is registered.
+    pub(crate) fn create_service<S: ServiceDefinition + ServiceMethods>(context: &RuntimeContext, service: S, instance_id: InstanceId) -> RunningService {
         let mut builder = MethodsBuilder { methods: vec![] };
 
         let service_id = S::id();
@@ -95,53 +314,294 @@ impl Runtime {
         S::register_methods(&mut builder);
 
         let methods = builder.methods;
+        let method_ids = methods.iter().map(|method| method.id).collect::<Vec<_>>();
+
+        let events = S::events();
 
-        self.vsomeip_config.services.push(VSomeIpServiceConfig {
-            id: service_id, 
-            conn_type: ConnectionType::Tcp(port),
-            ..Default::default()
+        let app = &context.application;
+
+        let health = Arc::new(ServiceHealth {
+            service_id,
+            instance_id,
+            policy: context.panic_policy,
+            services: context.services.clone(),
+            executor: context.executor.clone(),
+            degraded: AtomicBool::new(false),
         });
 
-        let creator = move |app: &vsomeip_rs::Application, instance_id: InstanceId| {
+        let (service, owned) = match S::CONCURRENCY {
+            Concurrency::ReadWrite => {
+                let service = Arc::new(RwLock::new(service));
+                (SharedService::Locked(service.clone()), OwnedService::Locked(service))
+            },
+            Concurrency::Unsynchronized => {
+                // Its handlers never wait for `on_start`, so it runs before they can be called
+                let mut service = service;
+                Self::start_service(&mut service, &health);
+
+                let service = Arc::new(service);
+                let in_flight = Arc::new(InFlight::default());
+                (SharedService::Unsynchronized(Arc::downgrade(&service), in_flight.clone()), OwnedService::Unsynchronized(service, in_flight))
+            },
+        };
+
+        for method in methods {
+            let service_clone = service.clone();
             let app_clone = app.clone();
+            let executor = context.executor.clone();
+            let health = health.clone();
+            let in_flight = context.in_flight.clone();
+
+            app.register_message_handler(service_id, instance_id, method.id, Box::new(move |request| {
+                let request = request.clone();
+                let app = app_clone.clone();
+                let health = health.clone();
+
+                let span = trace::request_span(&request);
+                let _entered = span.enter();
+
+                telemetry::request_received(&request);
+                let started = Instant::now();
 
-            app.register_state_handler(move |state| {
-                if state == State::Registered {
-                    app_clone.offer_service(service_id, instance_id, major_version, minor_version);
+                // Requests arriving during shutdown aren't handled anymore
+                let Some(guard) = in_flight.enter() else {
+                    return Self::handle_response(&app, &request, Err(SmipError::ServiceUnavailable), started);
+                };
+
+                // Panics must not unwind into vsomeip's callbacks
+                let service_clone = match &service_clone {
+                    SharedService::Locked(service) => service,
+                    SharedService::Unsynchronized(service, service_in_flight) => {
+                        let (Some(service_guard), Some(service)) = (service_in_flight.enter(), service.upgrade()) else {
+                            return Self::handle_response(&app, &request, Err(SmipError::ServiceUnavailable), started);
+                        };
+
+                        // Nothing is locked, so shared handlers always run on the dispatcher thread
+                        match method.f {
+                            Handler::Shared(f) => {
+                                let result = panic::catch_unwind(AssertUnwindSafe(|| f(&service, &request)));
+                                return Self::complete(&app, &request, &health, result, started);
+                            },
+                            Handler::Async(f) => {
+                                let response = AssertUnwindSafe(f(ServiceRef(ServiceRefInner::Unsynchronized(service)), request.clone())).catch_unwind();
+                                executor.spawn(Box::pin(async move {
+                                    let result = response.await;
+                                    Self::complete(&app, &request, &health, result, started);
+                                    drop(service_guard);
+                                    drop(guard);
+                                }.instrument(span.clone())));
+                                return;
+                            },
+                            Handler::Exclusive(_) => unreachable!("`MethodsBuilder` rejects `&mut self` methods of unsynchronized services"),
+                        }
+                    },
+                };
+
+                match method.f {
+                    Handler::Exclusive(f) => {
+                        // Run on the dispatcher thread unless the service is busy
+                        if let Ok(mut service) = service_clone.try_write() {
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| f(&mut service, &request)));
+                            return Self::complete(&app, &request, &health, result, started);
+                        }
+
+                        let service = service_clone.clone();
+                        executor.spawn(Box::pin(async move {
+                            let mut service = service.write().await;
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| f(&mut service, &request)));
+                            Self::complete(&app, &request, &health, result, started);
+                            drop(guard);
+                        }.instrument(span.clone())));
+                    },
+                    Handler::Shared(f) => {
+                        if let Ok(service) = service_clone.try_read() {
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| f(&service, &request)));
+                            return Self::complete(&app, &request, &health, result, started);
+                        }
+
+                        let service = service_clone.clone();
+                        executor.spawn(Box::pin(async move {
+                            let service = service.read().await;
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| f(&service, &request)));
+                            Self::complete(&app, &request, &health, result, started);
+                            drop(guard);
+                        }.instrument(span.clone())));
+                    },
+                    Handler::Async(f) => {
+                        let response = AssertUnwindSafe(f(ServiceRef(ServiceRefInner::Locked(service_clone.clone())), request.clone())).catch_unwind();
+                        executor.spawn(Box::pin(async move {
+                            let result = response.await;
+                            Self::complete(&app, &request, &health, result, started);
+                            drop(guard);
+                        }.instrument(span.clone())));
+                    },
+                }
+            }));
+        }
+
+        let offer = {
+            let app = app.clone();
+            let service = service.clone();
+            let executor = context.executor.clone();
+            let started = Arc::new(AtomicBool::new(false));
+
+            move || {
+                app.offer_service(service_id, instance_id, major_version, minor_version);
+
+                for event in &events {
+                    app.offer_event(service_id, instance_id, event.id, &[event.eventgroup], event.event_type);
                 }
-            });
 
-            let service = Arc::new(Mutex::new(service));
+                let binder = Self::event_binder(&app, service_id, instance_id);
+
+                match &service {
+                    SharedService::Locked(service) => {
+                        // Offering again after a pause or losing the routing manager doesn't start the service again
+                        let first = !started.swap(true, Ordering::SeqCst);
+
+                        // Bind and start on the dispatcher thread unless the service is busy, like its methods
+                        if first {
+                            if let Ok(mut service) = service.try_write() {
+                                service.bind_events(&binder);
+                                return Self::start_service(&mut *service, &health);
+                            }
+                        } else if let Ok(service) = service.try_read() {
+                            return service.bind_events(&binder);
+                        }
 
-            for method in methods {
-                let service_clone = service.clone();
-                let app_clone = app.clone();
+                        let service = service.clone();
+                        let health = health.clone();
+                        executor.spawn(Box::pin(async move {
+                            if first {
+                                let mut service = service.write().await;
+                                service.bind_events(&binder);
+                                Self::start_service(&mut *service, &health);
+                            } else {
+                                service.read().await.bind_events(&binder);
+                            }
+                        }));
+                    },
+                    SharedService::Unsynchronized(service, _) => {
+                        if let Some(service) = service.upgrade() {
+                            service.bind_events(&binder);
+                        }
+                    },
+                }
+            }
+        };
 
-                app.register_message_handler(service_id, instance_id, method.id, move |request| {
-                    let mut service = service_clone.lock();
-                    let result = (method.f)(&mut service, &request);
+        let stop_offer = {
+            let app = app.clone();
+            let event_ids = S::events().iter().map(|event| event.id).collect::<Vec<_>>();
 
-                    Self::handle_response(&app_clone, &request, result)
-                });
+            move || {
+                for event_id in &event_ids {
+                    app.stop_offer_event(service_id, instance_id, *event_id);
+                }
+
+                app.stop_offer_service(service_id, instance_id, major_version, minor_version);
             }
         };
 
-        self.service_creators.push(Box::new(creator));
+        let stop = {
+            let app = app.clone();
 
-        self
+            move |deadline: Instant| {
+                for method_id in method_ids {
+                    app.unregister_message_handler(service_id, instance_id, method_id);
+                }
+
+                // Waits for handlers that are still running, but not past the deadline
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match owned {
+                    OwnedService::Locked(service) => {
+                        let Either::Left((mut service, _)) = futures::executor::block_on(future::select(Box::pin(service.write()), Delay::new(timeout))) else {
+                            tracing::warn!("Skipping on_stop of service {:#06x} instance {:#06x}, its methods are still running", service_id, instance_id);
+                            return;
+                        };
+
+                        Self::run_hook("on_stop", service_id, instance_id, || service.on_stop());
+                    },
+                    OwnedService::Unsynchronized(service, in_flight) => {
+                        in_flight.close();
+
+                        // Handlers only hold the service while they run
+                        let service = in_flight.wait_drained(timeout).then(|| Arc::try_unwrap(service).ok()).flatten();
+                        let Some(mut service) = service else {
+                            tracing::warn!("Skipping on_stop of service {:#06x} instance {:#06x}, its methods are still running", service_id, instance_id);
+                            return;
+                        };
+
+                        Self::run_hook("on_stop", service_id, instance_id, || service.on_stop());
+                    },
+                }
+            }
+        };
+
+        RunningService {
+            service_id,
+            instance_id,
+            paused: false,
+            offer: Box::new(offer),
+            stop_offer: Box::new(stop_offer),
+            stop: Box::new(stop),
+        }
     }
-    pub fn run(self) -> Result<(), VSomeIpError> {
-        let config_str = self.vsomeip_config.build();
-        
-        let app = vsomeip_rs::Runtime::get().create_application_with(self.config.name, |_app| {
-            set_vsomeip_config(&config_str);
-        })?;
+    /// Starts all services on a separate thread, the returned handle controls them while they run.
+    pub fn start(self) -> Result<RuntimeHandle, BackendError> {
+        let application_config = ApplicationConfig {
+            name: self.config.name.clone(),
+            id: self.config.id,
+            services: self.services,
+        };
+        let app = self.config.create_backend()?.create_application(&application_config)?;
+
+        let executor = match self.executor {
+            Some(executor) => executor,
+            None => Arc::new(ThreadPool::new().expect("Failed to create thread pool")),
+        };
+
+        let services = Arc::new(Mutex::new(Services::default()));
+
+        let context = RuntimeContext {
+            application: app.clone(),
+            instance_id: self.config.instance_id,
+            executor,
+            in_flight: Arc::new(InFlight::default()),
+            panic_policy: self.config.panic_policy,
+            reserved: application_config.services.iter().map(|service| (service.id, service.instance_id)).collect(),
+            services: Arc::downgrade(&services),
+        };
 
         for creator in self.service_creators {
-            (creator)(&app, self.config.instance_id);
+            services.lock().running.push((creator)(&context));
         }
 
-        app.start();
+        let services_clone = services.clone();
+        app.register_state_handler(Box::new(move |state| {
+            let mut services = services_clone.lock();
+            services.registered = state == State::Registered;
+
+            if services.registered {
+                for service in services.running.iter().filter(|service| !service.paused) {
+                    (service.offer)();
+                }
+            }
+        }));
+
+        let app_clone = app.clone();
+        let thread = thread::spawn(move || app_clone.start());
+
+        Ok(RuntimeHandle {
+            context,
+            services,
+            shutdown_timeout: self.config.shutdown_timeout,
+            thread,
+        })
+    }
+    /// Starts all services and blocks until the application stops.
+    pub fn run(self) -> Result<(), BackendError> {
+        self.start()?.wait();
         Ok(())
     }
 }
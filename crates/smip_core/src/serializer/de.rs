@@ -0,0 +1,355 @@
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+
+use super::{EnumBase, Error, LengthField, Options, StringEncoding};
+
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+    options: Options,
+}
+
+enum Text<'de> {
+    Borrowed(&'de str),
+    Owned(String),
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(input: &'de [u8], options: Options) -> Self {
+        Self { input, options }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'de [u8], Error> {
+        if self.input.len() < len {
+            return Err(Error::Eof);
+        }
+
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        Ok(bytes)
+    }
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+
+        Ok(bytes)
+    }
+    fn read_length(&mut self, field: LengthField) -> Result<Option<usize>, Error> {
+        Ok(match field {
+            LengthField::None => None,
+            LengthField::U8 => Some(u8::from_be_bytes(self.take_array()?) as usize),
+            LengthField::U16 => Some(u16::from_be_bytes(self.take_array()?) as usize),
+            LengthField::U32 => Some(u32::from_be_bytes(self.take_array()?) as usize),
+        })
+    }
+    /// Splits off the data covered by a length field, without one the data
+    /// runs until the end of the payload.
+    fn region(&mut self, field: LengthField) -> Result<Deserializer<'de>, Error> {
+        let len = self.read_length(field)?.unwrap_or(self.input.len());
+
+        Ok(Deserializer::new(self.take(len)?, self.options))
+    }
+    fn read_variant(&mut self) -> Result<u32, Error> {
+        Ok(match self.options.enum_base {
+            EnumBase::U8 => u8::from_be_bytes(self.take_array()?) as u32,
+            EnumBase::U16 => u16::from_be_bytes(self.take_array()?) as u32,
+            EnumBase::U32 => u32::from_be_bytes(self.take_array()?),
+        })
+    }
+    fn read_text(&mut self) -> Result<Text<'de>, Error> {
+        let bytes = match self.read_length(self.options.string_length)? {
+            Some(len) => self.take(len)?,
+            None => {
+                // Without a length field the string runs up to and including its terminator
+                let unit = match self.options.string_encoding {
+                    StringEncoding::Utf8 => 1,
+                    StringEncoding::Utf16Be | StringEncoding::Utf16Le => 2,
+                };
+                let len = self.input
+                    .chunks(unit)
+                    .position(|chunk| chunk.iter().all(|byte| *byte == 0))
+                    .map(|ix| (ix + 1) * unit)
+                    .unwrap_or(self.input.len());
+
+                self.take(len)?
+            }
+        };
+
+        // The BOM takes precedence over the configured encoding
+        let (encoding, bytes) = match bytes {
+            [0xEF, 0xBB, 0xBF, rest @ ..] => (StringEncoding::Utf8, rest),
+            [0xFE, 0xFF, rest @ ..] => (StringEncoding::Utf16Be, rest),
+            [0xFF, 0xFE, rest @ ..] => (StringEncoding::Utf16Le, rest),
+            _ => (self.options.string_encoding, bytes),
+        };
+
+        match encoding {
+            StringEncoding::Utf8 => {
+                let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+
+                std::str::from_utf8(bytes)
+                    .map(Text::Borrowed)
+                    .map_err(|_| Error::InvalidString)
+            },
+            StringEncoding::Utf16Be | StringEncoding::Utf16Le => {
+                if bytes.len() % 2 != 0 {
+                    return Err(Error::InvalidString);
+                }
+
+                let mut units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|chunk| match encoding {
+                        StringEncoding::Utf16Be => u16::from_be_bytes([chunk[0], chunk[1]]),
+                        _ => u16::from_le_bytes([chunk[0], chunk[1]]),
+                    })
+                    .collect();
+
+                if units.last() == Some(&0) {
+                    units.pop();
+                }
+
+                String::from_utf16(&units)
+                    .map(Text::Owned)
+                    .map_err(|_| Error::InvalidString)
+            }
+        }
+    }
+    fn deserialize_compound<V: Visitor<'de>>(&mut self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.options.struct_length {
+            LengthField::None => visitor.visit_seq(FixedAccess { de: self, remaining: len }),
+            field => {
+                // Members following the known ones are skipped together with the region
+                let mut region = self.region(field)?;
+                visitor.visit_seq(FixedAccess { de: &mut region, remaining: len })
+            }
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::NotSelfDescribing)
+    }
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match u8::from_be_bytes(self.take_array()?) {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            value => Err(Error::InvalidBool(value)),
+        }
+    }
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(i8::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(i16::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(i32::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(i64::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(u8::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(u16::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(u32::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(u64::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(f32::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(f64::from_be_bytes(self.take_array()?))
+    }
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = u32::from_be_bytes(self.take_array()?);
+
+        visitor.visit_char(char::from_u32(value).ok_or(Error::InvalidChar(value))?)
+    }
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.read_text()? {
+            Text::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Text::Owned(text) => visitor.visit_string(text),
+        }
+    }
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let region = self.region(self.options.array_length)?;
+
+        visitor.visit_borrowed_bytes(region.input)
+    }
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match u8::from_be_bytes(self.take_array()?) {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            value => Err(Error::InvalidBool(value)),
+        }
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut region = self.region(self.options.array_length)?;
+
+        visitor.visit_seq(DynamicAccess { de: &mut region, entry_start: 0 })
+    }
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(FixedAccess { de: self, remaining: len })
+    }
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_compound(len, visitor)
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut region = self.region(self.options.array_length)?;
+
+        visitor.visit_map(DynamicAccess { de: &mut region, entry_start: 0 })
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_compound(fields.len(), visitor)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::NotSelfDescribing)
+    }
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::NotSelfDescribing)
+    }
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Elements of a tuple or struct, their count is known from the type.
+struct FixedAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for FixedAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Elements of an array or map, read until the region is exhausted.
+///
+/// Elements that don't consume any bytes would never exhaust it, so they are an error.
+struct DynamicAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    /// Bytes left in the region when the current map entry started.
+    entry_start: usize,
+}
+
+impl DynamicAccess<'_, '_> {
+    fn check_consumed(&self, start: usize) -> Result<(), Error> {
+        if self.de.input.len() == start {
+            return Err(Error::ZeroSizedElement);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for DynamicAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.de.input.is_empty() {
+            return Ok(None);
+        }
+
+        let start = self.de.input.len();
+        let value = seed.deserialize(&mut *self.de)?;
+        self.check_consumed(start)?;
+
+        Ok(Some(value))
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for DynamicAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if self.de.input.is_empty() {
+            return Ok(None);
+        }
+
+        self.entry_start = self.de.input.len();
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = seed.deserialize(&mut *self.de)?;
+        self.check_consumed(self.entry_start)?;
+
+        Ok(value)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let index = self.read_variant()?;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(index))?;
+
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_compound(len, visitor)
+    }
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_compound(fields.len(), visitor)
+    }
+}
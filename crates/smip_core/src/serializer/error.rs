@@ -0,0 +1,37 @@
+use std::fmt::Display;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{0}")]
+    Message(String),
+    #[error("Unexpected end of payload")]
+    Eof,
+    #[error("Length {0} does not fit into the configured length field")]
+    LengthOverflow(usize),
+    #[error("Variant index {0} does not fit into the configured enum base type")]
+    EnumOverflow(u32),
+    #[error("Invalid bool value {0}")]
+    InvalidBool(u8),
+    #[error("Invalid char value {0}")]
+    InvalidChar(u32),
+    #[error("Invalid string")]
+    InvalidString,
+    #[error("Payload can not be deserialized without knowing its type")]
+    NotSelfDescribing,
+    #[error("Elements of a dynamically sized array or map must not be zero-sized")]
+    ZeroSizedElement,
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
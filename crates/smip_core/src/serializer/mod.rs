@@ -0,0 +1,276 @@
+//! Serde implementation of the SOME/IP serialization rules.
+//!
+//! Integers and floats are written big-endian, strings, arrays and (optionally)
+//! structs are prefixed with a length field and enums are written as their
+//! variant index using a configurable base type. The layout can be adjusted
+//! per interface using [`Options`].
+//!
+//! A few types have no representation in the SOME/IP specification and use
+//! the following encodings: `Option<T>` is written as a `u8` flag followed by
+//! the value, `char` as a `u32` and enum variants carrying data as the variant
+//! index followed by the data.
+mod de;
+mod error;
+mod ser;
+
+pub use de::*;
+pub use error::*;
+pub use ser::*;
+
+use serde::{Deserialize, Serialize};
+
+/// Size of the length field written before dynamically sized data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LengthField {
+    None,
+    U8,
+    U16,
+    #[default]
+    U32,
+}
+
+impl LengthField {
+    pub(crate) fn size(self) -> usize {
+        match self {
+            LengthField::None => 0,
+            LengthField::U8 => 1,
+            LengthField::U16 => 2,
+            LengthField::U32 => 4,
+        }
+    }
+    pub(crate) fn max(self) -> usize {
+        match self {
+            LengthField::None => usize::MAX,
+            LengthField::U8 => u8::MAX as usize,
+            LengthField::U16 => u16::MAX as usize,
+            LengthField::U32 => u32::MAX as usize,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding {
+    #[default]
+    Utf8,
+    Utf16Be,
+    Utf16Le,
+}
+
+/// Base type used to write the variant index of an enum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EnumBase {
+    #[default]
+    U8,
+    U16,
+    U32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    pub string_length: LengthField,
+    pub string_encoding: StringEncoding,
+    pub array_length: LengthField,
+    pub struct_length: LengthField,
+    pub enum_base: EnumBase,
+}
+
+impl Options {
+    pub const fn new() -> Self {
+        Self {
+            string_length: LengthField::U32,
+            string_encoding: StringEncoding::Utf8,
+            array_length: LengthField::U32,
+            struct_length: LengthField::None,
+            enum_base: EnumBase::U8,
+        }
+    }
+    pub fn string_length(mut self, length: LengthField) -> Self {
+        self.string_length = length;
+        self
+    }
+    pub fn string_encoding(mut self, encoding: StringEncoding) -> Self {
+        self.string_encoding = encoding;
+        self
+    }
+    pub fn array_length(mut self, length: LengthField) -> Self {
+        self.array_length = length;
+        self
+    }
+    pub fn struct_length(mut self, length: LengthField) -> Self {
+        self.struct_length = length;
+        self
+    }
+    pub fn enum_base(mut self, base: EnumBase) -> Self {
+        self.enum_base = base;
+        self
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    to_vec_with(value, Options::default())
+}
+
+pub fn to_vec_with<T: Serialize + ?Sized>(value: &T, options: Options) -> Result<Vec<u8>, Error> {
+    let mut serializer = Serializer::new(options);
+    value.serialize(&mut serializer)?;
+
+    Ok(serializer.into_inner())
+}
+
+/// Deserializes a value from the start of `input`, trailing bytes are ignored.
+pub fn from_slice<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T, Error> {
+    from_slice_with(input, Options::default())
+}
+
+pub fn from_slice_with<'de, T: Deserialize<'de>>(input: &'de [u8], options: Options) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(input, options);
+
+    T::deserialize(&mut deserializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: u16,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Gear {
+        Park,
+        Reverse,
+        Neutral,
+        Drive,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Status {
+        name: String,
+        gear: Gear,
+        points: Vec<Point>,
+        speed: Option<f32>,
+        flags: [bool; 2],
+    }
+
+    #[test]
+    fn test_integers_are_big_endian() {
+        assert_eq!(to_vec(&0x1234u16).unwrap(), [0x12, 0x34]);
+        assert_eq!(to_vec(&-2i32).unwrap(), [0xFF, 0xFF, 0xFF, 0xFE]);
+        assert_eq!(to_vec(&0x0102030405060708u64).unwrap(), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(to_vec(&1.0f32).unwrap(), [0x3F, 0x80, 0x00, 0x00]);
+        assert_eq!(to_vec(&true).unwrap(), [0x01]);
+
+        assert_eq!(from_slice::<u16>(&[0x12, 0x34]).unwrap(), 0x1234);
+        assert_eq!(from_slice::<i32>(&[0xFF, 0xFF, 0xFF, 0xFE]).unwrap(), -2);
+    }
+
+    #[test]
+    fn test_utf8_string() {
+        let bytes = to_vec("Hi").unwrap();
+
+        assert_eq!(bytes, [0, 0, 0, 6, 0xEF, 0xBB, 0xBF, b'H', b'i', 0]);
+        assert_eq!(from_slice::<String>(&bytes).unwrap(), "Hi");
+        assert_eq!(from_slice::<&str>(&bytes).unwrap(), "Hi");
+    }
+
+    #[test]
+    fn test_utf16_string() {
+        let options = Options::new()
+            .string_encoding(StringEncoding::Utf16Le)
+            .string_length(LengthField::U8);
+
+        let bytes = to_vec_with("Hi", options).unwrap();
+
+        assert_eq!(bytes, [8, 0xFF, 0xFE, b'H', 0, b'i', 0, 0, 0]);
+        assert_eq!(from_slice_with::<String>(&bytes, options).unwrap(), "Hi");
+
+        // The BOM decides the byte order when reading
+        let big_endian = [8, 0xFE, 0xFF, 0, b'H', 0, b'i', 0, 0];
+        assert_eq!(from_slice_with::<String>(&big_endian, options).unwrap(), "Hi");
+    }
+
+    #[test]
+    fn test_array_length_is_in_bytes() {
+        let bytes = to_vec(&vec![1u16, 2, 3]).unwrap();
+
+        assert_eq!(bytes, [0, 0, 0, 6, 0, 1, 0, 2, 0, 3]);
+        assert_eq!(from_slice::<Vec<u16>>(&bytes).unwrap(), [1, 2, 3]);
+
+        // Fixed size arrays have no length field
+        assert_eq!(to_vec(&[1u8, 2]).unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn test_struct_length_field() {
+        let point = Point { x: 1, y: 2 };
+
+        assert_eq!(to_vec(&point).unwrap(), [0, 1, 0, 0, 0, 2]);
+
+        let options = Options::new().struct_length(LengthField::U16);
+        let bytes = to_vec_with(&point, options).unwrap();
+        assert_eq!(bytes, [0, 6, 0, 1, 0, 0, 0, 2]);
+
+        // Members appended by a newer version of the interface are skipped
+        let extended = [0, 8, 0, 1, 0, 0, 0, 2, 0xAA, 0xBB, 0xCC];
+        let (point, next): (Point, u8) = from_slice_with(&extended, options).unwrap();
+        assert_eq!(point, Point { x: 1, y: 2 });
+        assert_eq!(next, 0xCC);
+    }
+
+    #[test]
+    fn test_enum_base() {
+        assert_eq!(to_vec(&Gear::Drive).unwrap(), [3]);
+
+        let options = Options::new().enum_base(EnumBase::U16);
+        assert_eq!(to_vec_with(&Gear::Reverse, options).unwrap(), [0, 1]);
+        assert_eq!(from_slice_with::<Gear>(&[0, 2], options).unwrap(), Gear::Neutral);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let status = Status {
+            name: "Dashboard".into(),
+            gear: Gear::Drive,
+            points: vec![Point { x: 1, y: -1 }, Point { x: 2, y: -2 }],
+            speed: Some(12.5),
+            flags: [true, false],
+        };
+
+        let bytes = to_vec(&status).unwrap();
+        assert_eq!(from_slice::<Status>(&bytes).unwrap(), status);
+
+        let map = BTreeMap::from([(1u8, "one".to_string()), (2, "two".to_string())]);
+        let bytes = to_vec(&map).unwrap();
+        assert_eq!(from_slice::<BTreeMap<u8, String>>(&bytes).unwrap(), map);
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = Options::new().array_length(LengthField::U8);
+
+        assert!(matches!(to_vec_with(&vec![0u32; 64], options), Err(Error::LengthOverflow(256))));
+        assert!(matches!(from_slice::<u32>(&[0, 1]), Err(Error::Eof)));
+        assert!(matches!(from_slice::<Vec<u16>>(&[0, 0, 0, 4, 0, 1]), Err(Error::Eof)));
+    }
+
+    #[test]
+    fn test_zero_sized_elements() {
+        // They would never exhaust the array, however long it claims to be
+        assert!(matches!(from_slice::<Vec<()>>(&[0, 0, 0, 2, 0, 0]), Err(Error::ZeroSizedElement)));
+        assert!(matches!(from_slice::<BTreeMap<(), ()>>(&[0, 0, 0, 1, 0]), Err(Error::ZeroSizedElement)));
+        assert_eq!(from_slice::<Vec<()>>(&[0, 0, 0, 0]).unwrap(), []);
+    }
+}
@@ -0,0 +1,342 @@
+use serde::{ser, Serialize};
+
+use super::{EnumBase, Error, LengthField, Options, StringEncoding};
+
+pub struct Serializer {
+    output: Vec<u8>,
+    options: Options,
+}
+
+impl Serializer {
+    pub fn new(options: Options) -> Self {
+        Self {
+            output: Vec::new(),
+            options,
+        }
+    }
+    pub fn into_inner(self) -> Vec<u8> {
+        self.output
+    }
+
+    /// Reserves space for a length field, which is filled in by `end_length`
+    /// once the size of the data that follows is known.
+    fn begin_length(&mut self, field: LengthField) -> Length {
+        let position = self.output.len();
+        self.output.resize(position + field.size(), 0);
+
+        Length { position, field }
+    }
+    fn end_length(&mut self, length: Length) -> Result<(), Error> {
+        let start = length.position + length.field.size();
+        let size = self.output.len() - start;
+
+        if size > length.field.max() {
+            return Err(Error::LengthOverflow(size));
+        }
+
+        let target = &mut self.output[length.position..start];
+        match length.field {
+            LengthField::None => {},
+            LengthField::U8 => target.copy_from_slice(&(size as u8).to_be_bytes()),
+            LengthField::U16 => target.copy_from_slice(&(size as u16).to_be_bytes()),
+            LengthField::U32 => target.copy_from_slice(&(size as u32).to_be_bytes()),
+        }
+
+        Ok(())
+    }
+    fn write_variant(&mut self, index: u32) -> Result<(), Error> {
+        match self.options.enum_base {
+            EnumBase::U8 => {
+                let index = u8::try_from(index).map_err(|_| Error::EnumOverflow(index))?;
+                self.output.push(index);
+            },
+            EnumBase::U16 => {
+                let index = u16::try_from(index).map_err(|_| Error::EnumOverflow(index))?;
+                self.output.extend_from_slice(&index.to_be_bytes());
+            },
+            EnumBase::U32 => self.output.extend_from_slice(&index.to_be_bytes()),
+        }
+
+        Ok(())
+    }
+    fn compound(&mut self, field: LengthField) -> Compound<'_> {
+        let length = self.begin_length(field);
+
+        Compound { ser: self, length }
+    }
+}
+
+struct Length {
+    position: usize,
+    field: LengthField,
+}
+
+pub struct Compound<'a> {
+    ser: &'a mut Serializer,
+    length: Length,
+}
+
+impl<'a> Compound<'a> {
+    fn end(self) -> Result<(), Error> {
+        self.ser.end_length(self.length)
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.output.push(v as u8);
+        Ok(())
+    }
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.output.push(v);
+        Ok(())
+    }
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.output.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_u32(v as u32)
+    }
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        let length = self.begin_length(self.options.string_length);
+
+        // Strings always start with a BOM and end with a terminator, both count towards the length
+        match self.options.string_encoding {
+            StringEncoding::Utf8 => {
+                self.output.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+                self.output.extend_from_slice(v.as_bytes());
+                self.output.push(0);
+            },
+            StringEncoding::Utf16Be => {
+                self.output.extend_from_slice(&[0xFE, 0xFF]);
+                for unit in v.encode_utf16() {
+                    self.output.extend_from_slice(&unit.to_be_bytes());
+                }
+                self.output.extend_from_slice(&[0, 0]);
+            },
+            StringEncoding::Utf16Le => {
+                self.output.extend_from_slice(&[0xFF, 0xFE]);
+                for unit in v.encode_utf16() {
+                    self.output.extend_from_slice(&unit.to_le_bytes());
+                }
+                self.output.extend_from_slice(&[0, 0]);
+            },
+        }
+
+        self.end_length(length)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        let length = self.begin_length(self.options.array_length);
+        self.output.extend_from_slice(v);
+
+        self.end_length(length)
+    }
+    fn serialize_none(self) -> Result<(), Error> {
+        self.output.push(0);
+        Ok(())
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        self.output.push(1);
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str) -> Result<(), Error> {
+        self.write_variant(variant_index)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_variant(variant_index)?;
+        value.serialize(self)
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, Error> {
+        let field = self.options.array_length;
+        Ok(self.compound(field))
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, Error> {
+        // Tuples and fixed size arrays have a static size
+        Ok(self.compound(LengthField::None))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>, Error> {
+        let field = self.options.struct_length;
+        Ok(self.compound(field))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.write_variant(variant_index)?;
+
+        let field = self.options.struct_length;
+        Ok(self.compound(field))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, Error> {
+        let field = self.options.array_length;
+        Ok(self.compound(field))
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>, Error> {
+        let field = self.options.struct_length;
+        Ok(self.compound(field))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.write_variant(variant_index)?;
+
+        let field = self.options.struct_length;
+        Ok(self.compound(field))
+    }
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut *self.ser)
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
@@ -0,0 +1,55 @@
+use std::{io, sync::atomic::{AtomicI32, Ordering}};
+
+use libc::c_int;
+
+/// Write end of the pipe the signal handler reports to.
+static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_signal(_signal: c_int) {
+    let fd = PIPE.load(Ordering::Relaxed);
+    if fd >= 0 {
+        // Only async-signal-safe calls are allowed here
+        unsafe { libc::write(fd, [1u8].as_ptr().cast(), 1) };
+    }
+}
+
+/// Blocks until the process receives SIGINT or SIGTERM.
+pub(crate) fn wait_for_signal() -> io::Result<()> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let [read, write] = fds;
+    PIPE.store(write, Ordering::Relaxed);
+
+    let handler = on_signal as extern "C" fn(c_int) as libc::sighandler_t;
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    let mut byte = 0u8;
+    let result = loop {
+        match unsafe { libc::read(read, (&mut byte as *mut u8).cast(), 1) } {
+            1 => break Ok(()),
+            _ => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    break Err(err);
+                }
+            }
+        }
+    };
+
+    PIPE.store(-1, Ordering::Relaxed);
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+        libc::signal(libc::SIGTERM, libc::SIG_DFL);
+        libc::close(read);
+        libc::close(write);
+    }
+
+    result
+}
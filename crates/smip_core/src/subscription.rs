@@ -0,0 +1,57 @@
+use std::{sync::mpsc, time::Duration};
+
+use someip_types::*;
+
+use crate::{Client, PayloadCodec, SmipError, SomeIpCodec};
+
+/// Active subscription to an event, unsubscribes when dropped.
+pub struct Subscription<'c, C: PayloadCodec = SomeIpCodec> {
+    pub(crate) client: &'c Client<C>,
+    pub(crate) eventgroup: EventGroupId,
+    pub(crate) event: EventId,
+    pub(crate) token: u64,
+}
+
+impl<C: PayloadCodec> Subscription<'_, C> {
+    pub fn eventgroup(&self) -> EventGroupId {
+        self.eventgroup
+    }
+    pub fn event(&self) -> EventId {
+        self.event
+    }
+}
+
+impl<C: PayloadCodec> Drop for Subscription<'_, C> {
+    fn drop(&mut self) {
+        self.client.unsubscribe(self.eventgroup, self.event, self.token);
+    }
+}
+
+/// Subscription that queues the decoded notifications instead of passing them to a callback.
+pub struct EventReceiver<'c, T, C: PayloadCodec = SomeIpCodec> {
+    pub(crate) subscription: Subscription<'c, C>,
+    pub(crate) receiver: mpsc::Receiver<Result<T, SmipError>>,
+}
+
+impl<'c, T, C: PayloadCodec> EventReceiver<'c, T, C> {
+    pub fn subscription(&self) -> &Subscription<'c, C> {
+        &self.subscription
+    }
+    pub fn recv(&self) -> Option<Result<T, SmipError>> {
+        self.receiver.recv().ok()
+    }
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Result<T, SmipError>> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+    pub fn try_recv(&self) -> Option<Result<T, SmipError>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl<T, C: PayloadCodec> Iterator for EventReceiver<'_, T, C> {
+    type Item = Result<T, SmipError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::Instant;
+
+use someip_types::*;
+
+use crate::{error::SmipError, types::*};
+
+/// Labels identifying the method a message belongs to.
+fn labels(message: &Message) -> [(&'static str, String); 3] {
+    [
+        ("service", format!("{:#06x}", message.get_service())),
+        ("instance", format!("{:#06x}", message.get_instance())),
+        ("method", format!("{:#06x}", message.get_method())),
+    ]
+}
+
+/// Counts a request received by the [`Runtime`](crate::Runtime) and its payload.
+pub(crate) fn request_received(request: &Message) {
+    let labels = labels(request);
+
+    metrics::counter!("smip_requests_total", &labels).increment(1);
+    metrics::counter!("smip_request_bytes_total", &labels).increment(request.get_payload().get_data().len() as u64);
+}
+
+/// Records how long a request took from its arrival until it was answered, including the time
+/// spent waiting for the service lock and the executor, and the response it was answered with, if any.
+pub(crate) fn request_completed(request: &Message, response: Option<&Message>, started: Instant) {
+    let labels = labels(request);
+
+    metrics::histogram!("smip_request_duration_seconds", &labels).record(started.elapsed());
+
+    let Some(response) = response else {
+        return;
+    };
+
+    metrics::counter!("smip_response_bytes_total", &labels).increment(response.get_payload().get_data().len() as u64);
+
+    if response.get_message_type() == MessageType::Error || response.get_return_code() != ReturnCode::Ok {
+        let [service, instance, method] = labels;
+        let return_code = ("return_code", format!("{:?}", response.get_return_code()));
+
+        metrics::counter!("smip_request_errors_total", &[service, instance, method, return_code]).increment(1);
+    }
+}
+
+/// Counts a request sent by a [`Client`](crate::Client) and its payload.
+pub(crate) fn call_sent(request: &Message) {
+    let labels = labels(request);
+
+    metrics::counter!("smip_client_calls_total", &labels).increment(1);
+    metrics::counter!("smip_client_request_bytes_total", &labels).increment(request.get_payload().get_data().len() as u64);
+}
+
+/// Counts an attempt of a call that wasn't answered within its timeout.
+pub(crate) fn attempt_timed_out(request: &Message) {
+    metrics::counter!("smip_client_timeouts_total", &labels(request)).increment(1);
+}
+
+/// Records the outcome of a call, including all of its retries.
+pub(crate) fn call_completed(request: &Message, result: &Result<Message, SmipError>, started: Instant) {
+    let labels = labels(request);
+
+    metrics::histogram!("smip_client_call_duration_seconds", &labels).record(started.elapsed());
+
+    let error = match result {
+        Ok(response) => {
+            metrics::counter!("smip_client_response_bytes_total", &labels).increment(response.get_payload().get_data().len() as u64);
+            return;
+        },
+        Err(SmipError::Timeout) => "Timeout".to_owned(),
+        Err(SmipError::ServiceUnavailable) => "ServiceUnavailable".to_owned(),
+        Err(SmipError::Transport(code) | SmipError::Remote { code, .. }) => format!("{:?}", code),
+        Err(_) => "Other".to_owned(),
+    };
+
+    let [service, instance, method] = labels;
+    metrics::counter!("smip_client_errors_total", &[service, instance, method, ("error", error)]).increment(1);
+}
+
+#[cfg(feature = "prometheus")]
+pub use exporter::MetricsExporter;
+
+#[cfg(feature = "prometheus")]
+mod exporter {
+    use std::{net::SocketAddr, path::PathBuf, thread, time::Duration};
+
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    /// Built-in exporter publishing the metrics of all runtimes and clients in the
+    /// Prometheus text format.
+    ///
+    /// The exporter is installed as the global `metrics` recorder, so it can't be
+    /// combined with another one.
+    #[derive(Debug, Clone)]
+    pub enum MetricsExporter {
+        /// Serves the metrics over HTTP on the given address.
+        Http(SocketAddr),
+        /// Writes the metrics to a file every `interval`, for example for the
+        /// textfile collector of the node exporter.
+        File {
+            path: PathBuf,
+            interval: Duration,
+        },
+    }
+
+    impl MetricsExporter {
+        pub fn install(self) -> anyhow::Result<()> {
+            match self {
+                MetricsExporter::Http(addr) => {
+                    PrometheusBuilder::new().with_http_listener(addr).install()?;
+                },
+                MetricsExporter::File { path, interval } => {
+                    let handle = PrometheusBuilder::new().install_recorder()?;
+
+                    thread::Builder::new()
+                        .name("smip-metrics".to_owned())
+                        .spawn(move || {
+                            // Written next to the file and renamed, so readers never see a partial file
+                            let temp_path = path.with_extension("tmp");
+
+                            loop {
+                                thread::sleep(interval);
+                                handle.run_upkeep();
+
+                                let result = std::fs::write(&temp_path, handle.render())
+                                    .and_then(|_| std::fs::rename(&temp_path, &path));
+                                if let Err(err) = result {
+                                    tracing::warn!("Failed to write metrics to {}: {}", path.display(), err);
+                                }
+                            }
+                        })?;
+                },
+            }
+
+            Ok(())
+        }
+    }
+}
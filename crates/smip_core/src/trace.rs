@@ -0,0 +1,63 @@
+use std::time::Instant;
+
+use someip_types::*;
+use tracing::{field::Empty, Span};
+
+use crate::types::*;
+
+/// Span of a request handled by the [`Runtime`](crate::Runtime).
+///
+/// The generated handlers fill in how long decoding the arguments, the method
+/// itself and encoding the response took, the runtime the return code it answered with.
+pub(crate) fn request_span(request: &Message) -> Span {
+    tracing::info_span!(
+        "smip.request",
+        service = request.get_service(),
+        instance = request.get_instance(),
+        method = request.get_method(),
+        client = request.get_client(),
+        session = request.get_session(),
+        message_type = ?request.get_message_type(),
+        return_code = Empty,
+        decode_us = Empty,
+        handler_us = Empty,
+        encode_us = Empty,
+    )
+}
+
+/// Span of a call made by a [`Client`](crate::Client), the client and session id
+/// are filled in once vsomeip sent the request.
+pub(crate) fn call_span(request: &Message) -> Span {
+    tracing::info_span!(
+        "smip.call",
+        service = request.get_service(),
+        instance = request.get_instance(),
+        method = request.get_method(),
+        message_type = ?request.get_message_type(),
+        reliable = request.is_reliable(),
+        client = Empty,
+        session = Empty,
+        attempts = Empty,
+        return_code = Empty,
+    )
+}
+
+/// Span of a notification received by a [`Client`](crate::Client).
+pub(crate) fn notification_span(notification: &Message) -> Span {
+    tracing::debug_span!(
+        "smip.notification",
+        service = notification.get_service(),
+        instance = notification.get_instance(),
+        event = notification.get_method(),
+        session = notification.get_session(),
+    )
+}
+
+/// Records the microseconds passed since `since` in a field of the current span.
+pub fn record_elapsed(field: &'static str, since: Instant) {
+    Span::current().record(field, since.elapsed().as_micros() as u64);
+}
+
+pub(crate) fn record_return_code(span: &Span, return_code: ReturnCode) {
+    span.record("return_code", tracing::field::debug(return_code));
+}
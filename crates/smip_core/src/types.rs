@@ -1,52 +1,168 @@
-use bincode::Options;
+use std::{ops::Deref, sync::Arc};
+
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use someip_types::*;
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+use crate::{codec::*, error::SmipError, event::{EventBinder, EventDefinition}};
 
-use crate::error::SmipError;
+pub(crate) use smip_al::{Message, Payload};
 
-pub type Message = vsomeip_rs::Message;
-pub type Application = vsomeip_rs::Application;
-pub type Payload = vsomeip_rs::Payload;
+/// The application of the backend a runtime or client sends and receives its messages with.
+pub(crate) type Application = Arc<dyn smip_al::Application>;
 
 pub type RequestCallback<S> = fn(&mut S, &Message) -> Result<Option<Message>, SmipError>;
+/// Handler of a `&self` method, runs concurrently with other shared handlers.
+pub type SharedRequestCallback<S> = fn(&S, &Message) -> Result<Option<Message>, SmipError>;
+/// Handler of an `async` method, it locks the service itself for as long as the method needs it.
+///
+/// `#[methods_impl]` keeps the lock across every `.await` of the method, so an
+/// `async fn(&mut self)` blocks all other methods of the service until its future
+/// completes. Slow calls are better awaited in `&self` methods of a service that
+/// keeps its mutable state behind its own, briefly held locks.
+pub type AsyncRequestCallback<S> = fn(ServiceRef<S>, Message) -> BoxFuture<'static, Result<Option<Message>, SmipError>>;
+
+/// A running service as passed to the handlers of `async` methods.
+pub struct ServiceRef<S>(pub(crate) ServiceRefInner<S>);
+
+pub(crate) enum ServiceRefInner<S> {
+    Locked(Arc<RwLock<S>>),
+    Unsynchronized(Arc<S>),
+}
+
+impl<S> ServiceRef<S> {
+    /// Shares the service with other `&self` methods, unsynchronized services aren't locked at all.
+    pub async fn read_owned(self) -> ServiceReadGuard<S> {
+        match self.0 {
+            ServiceRefInner::Locked(service) => ServiceReadGuard(ServiceReadGuardInner::Locked(service.read_owned().await)),
+            ServiceRefInner::Unsynchronized(service) => ServiceReadGuard(ServiceReadGuardInner::Unsynchronized(service)),
+        }
+    }
+    /// Locks the service for a `&mut self` method.
+    ///
+    /// # Panics
+    /// If the service is [`Concurrency::Unsynchronized`], which doesn't have `&mut self` methods.
+    pub async fn write_owned(self) -> OwnedRwLockWriteGuard<S> {
+        match self.0 {
+            ServiceRefInner::Locked(service) => service.write_owned().await,
+            ServiceRefInner::Unsynchronized(_) => panic!("unsynchronized services can't be locked for writing"),
+        }
+    }
+}
+
+/// Shared access to a service, returned by [`ServiceRef::read_owned`].
+pub struct ServiceReadGuard<S>(ServiceReadGuardInner<S>);
+
+enum ServiceReadGuardInner<S> {
+    Locked(OwnedRwLockReadGuard<S>),
+    Unsynchronized(Arc<S>),
+}
+
+impl<S> Deref for ServiceReadGuard<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        match &self.0 {
+            ServiceReadGuardInner::Locked(service) => service,
+            ServiceReadGuardInner::Unsynchronized(service) => service,
+        }
+    }
+}
+
+pub(crate) enum Handler<S> {
+    Exclusive(RequestCallback<S>),
+    Shared(SharedRequestCallback<S>),
+    Async(AsyncRequestCallback<S>),
+}
 pub(crate) struct Method<S> {
     pub id: MethodId,
-    pub f: RequestCallback<S>
+    pub f: Handler<S>
 }
 pub struct MethodsBuilder<S> {
     pub(crate) methods: Vec<Method<S>>
 }
 
 impl<S: ServiceDefinition> MethodsBuilder<S> {
+    /// Adds a `&mut self` method, which [`Concurrency::Unsynchronized`] services can't have.
+    ///
+    /// Handlers run on the dispatcher thread if the service isn't locked and are
+    /// otherwise queued on the executor until it is free. Responses of queued requests
+    /// can therefore be sent after the responses of requests that arrived later,
+    /// clients match them by request id.
     pub fn add_method(&mut self, id: MethodId, f: RequestCallback<S>) {
+        assert!(S::CONCURRENCY != Concurrency::Unsynchronized, "method {:#06x} takes `&mut self`, which unsynchronized services don't support", id);
+
+        self.push(id, Handler::Exclusive(f));
+    }
+    /// Adds a `&self` method, it's queued like [`Self::add_method`] while a `&mut self` method runs.
+    pub fn add_shared_method(&mut self, id: MethodId, f: SharedRequestCallback<S>) {
+        self.push(id, Handler::Shared(f));
+    }
+    pub fn add_async_method(&mut self, id: MethodId, f: AsyncRequestCallback<S>) {
+        self.push(id, Handler::Async(f));
+    }
+    fn push(&mut self, id: MethodId, f: Handler<S>) {
+        assert!(!self.methods.iter().any(|method| method.id == id), "method id {:#06x} is registered more than once", id);
+
         self.methods.push(Method {id, f});
     }
 }
+/// How access to the state of a service is synchronized between its methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Concurrency {
+    /// `&self` methods share a read lock, `&mut self` methods take the write lock.
+    #[default]
+    ReadWrite,
+    /// The service manages interior mutability itself, only `&self` methods
+    /// are allowed and they use the service without taking a lock.
+    ///
+    /// Its `on_start` hook runs when the service is added to the runtime instead
+    /// of when it's first offered, `on_stop` is skipped if methods are still running.
+    Unsynchronized,
+}
+
+/// Services that can have `&mut self` methods, implemented by `#[service]`
+/// unless the service is [`Concurrency::Unsynchronized`].
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is an unsynchronized service, its methods can't take `&mut self`",
+    label = "takes `&mut self`"
+)]
+pub trait Synchronized: ServiceDefinition {}
+
 pub trait ServiceDefinition: Send + Sync + 'static {
+    type Codec: PayloadCodec;
+
+    const CONCURRENCY: Concurrency = Concurrency::ReadWrite;
+
     fn id() -> ServiceId;
     fn major_version() -> MajorVersion;
     fn minor_version() -> MinorVersion;
+    fn events() -> Vec<EventDefinition> {
+        Vec::new()
+    }
+    fn bind_events(&self, _binder: &EventBinder<Self>) where Self: Sized {}
+    fn register_fields(_builder: &mut MethodsBuilder<Self>) where Self: Sized {}
 }
 
 pub trait ServiceMethods {
     fn register_methods(builder: &mut MethodsBuilder<Self>) where Self: Sized;
+    /// Called once the service is offered.
+    fn on_start(&mut self) {}
+    /// Called when the runtime shuts down, after the service stopped being offered.
+    fn on_stop(&mut self) {}
 }
 
 pub trait FromPayload<'de>: Deserialize<'de> {
     fn from_payload(payload: &'de [u8]) -> Result<Self, SmipError> {
-        let mut de = bincode::Deserializer::from_slice(
-            payload,
-            bincode::options().with_fixint_encoding().allow_trailing_bytes(),
-        );
-
-        Self::deserialize(&mut de).map_err(|err| SmipError::FromPayloadError(err))
+        SomeIpCodec::decode(payload)
     }
 }
 
 
 pub trait ToPayload: Serialize {
     fn to_payload(&self) -> Result<Vec<u8>, SmipError> {
-        bincode::serialize(self).map_err(|err| SmipError::ToPayloadError(err))
+        SomeIpCodec::encode(self)
     }
 }
 
@@ -0,0 +1,549 @@
+//! Runtimes and clients exchanging messages over the in-process loopback backend.
+
+use std::{sync::{atomic::{AtomicBool, AtomicU32, Ordering}, Arc}, time::{Duration, Instant}};
+
+use smip_core::*;
+use someip_types::*;
+
+const SERVICE_ID: ServiceId = 0x1234;
+const ADD: MethodId = 0x0001;
+const SUBTRACT: MethodId = 0x0002;
+const EXPLODE: MethodId = 0x0003;
+const HOLD: MethodId = 0x0004;
+const TOTAL: EventId = 0x8001;
+const TARGET: EventId = 0x8002;
+const EVENTGROUP: EventGroupId = 0x0001;
+
+#[derive(Default)]
+struct Counter {
+    total: u32,
+    total_event: Event<u32>,
+}
+
+impl ServiceDefinition for Counter {
+    type Codec = SomeIpCodec;
+
+    fn id() -> ServiceId {
+        SERVICE_ID
+    }
+    fn major_version() -> MajorVersion {
+        1
+    }
+    fn minor_version() -> MinorVersion {
+        0
+    }
+    fn events() -> Vec<EventDefinition> {
+        vec![EventDefinition { id: TOTAL, eventgroup: EVENTGROUP, event_type: EventType::Event }]
+    }
+    fn bind_events(&self, binder: &EventBinder<Self>) {
+        self.total_event.bind(binder, TOTAL);
+    }
+}
+
+impl ServiceMethods for Counter {
+    fn register_methods(builder: &mut MethodsBuilder<Self>) {
+        builder.add_method(ADD, |counter, request| {
+            let value: u32 = SomeIpCodec::decode(request.get_payload().get_data())?;
+            counter.total += value;
+            counter.total_event.notify(&counter.total)?;
+
+            let mut response = Message::response(request);
+            response.set_payload(&Payload::with_data(&SomeIpCodec::encode(&counter.total)?));
+
+            Ok(Some(response))
+        });
+        builder.add_method(SUBTRACT, |counter, request| {
+            let value: u32 = SomeIpCodec::decode(request.get_payload().get_data())?;
+            let Some(total) = counter.total.checked_sub(value) else {
+                return error_response::<SomeIpCodec, _>(request, &Overdrawn { total: counter.total }).map(Some);
+            };
+            counter.total = total;
+
+            let mut response = Message::response(request);
+            response.set_payload(&Payload::with_data(&SomeIpCodec::encode(&counter.total)?));
+
+            Ok(Some(response))
+        });
+        builder.add_method(EXPLODE, |_, _| panic!("method panicked"));
+        builder.add_async_method(HOLD, |counter, request| Box::pin(async move {
+            let _counter = counter.write_owned().await;
+            futures_timer::Delay::new(Duration::from_millis(500)).await;
+
+            Ok(Some(Message::response(&request)))
+        }));
+    }
+}
+
+/// Error of a subtraction below zero, carrying the current total.
+struct Overdrawn {
+    total: u32,
+}
+
+impl SmipMethodError for Overdrawn {
+    fn return_code(&self) -> ReturnCode {
+        ReturnCode::Application(0x20)
+    }
+    fn error_payload<C: PayloadCodec>(&self) -> Result<Option<Vec<u8>>, SmipError> {
+        C::encode(&self.total).map(Some)
+    }
+}
+
+/// Service with a field, whose notifier sends the current value to new subscribers.
+struct Thermostat {
+    target: Field<u32>,
+}
+
+impl ServiceDefinition for Thermostat {
+    type Codec = SomeIpCodec;
+
+    fn id() -> ServiceId {
+        SERVICE_ID
+    }
+    fn major_version() -> MajorVersion {
+        1
+    }
+    fn minor_version() -> MinorVersion {
+        0
+    }
+    fn events() -> Vec<EventDefinition> {
+        vec![EventDefinition { id: TARGET, eventgroup: EVENTGROUP, event_type: EventType::Field }]
+    }
+    fn bind_events(&self, binder: &EventBinder<Self>) {
+        self.target.bind(binder, TARGET);
+    }
+}
+
+impl ServiceMethods for Thermostat {
+    fn register_methods(builder: &mut MethodsBuilder<Self>) {
+        builder.add_shared_method(ADD, |thermostat, request| {
+            let mut response = Message::response(request);
+            response.set_payload(&Payload::with_data(&SomeIpCodec::encode(thermostat.target.get())?));
+
+            Ok(Some(response))
+        });
+    }
+}
+
+/// Service whose `on_stop` hook panics.
+struct Failing;
+
+impl ServiceDefinition for Failing {
+    type Codec = SomeIpCodec;
+
+    fn id() -> ServiceId {
+        0x4321
+    }
+    fn major_version() -> MajorVersion {
+        1
+    }
+    fn minor_version() -> MinorVersion {
+        0
+    }
+}
+
+impl ServiceMethods for Failing {
+    fn register_methods(_builder: &mut MethodsBuilder<Self>) {}
+    fn on_stop(&mut self) {
+        panic!("on_stop failed");
+    }
+}
+
+/// Counter managing its own synchronization.
+#[derive(Default)]
+struct AtomicCounter {
+    total: AtomicU32,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ServiceDefinition for AtomicCounter {
+    type Codec = SomeIpCodec;
+
+    const CONCURRENCY: Concurrency = Concurrency::Unsynchronized;
+
+    fn id() -> ServiceId {
+        SERVICE_ID
+    }
+    fn major_version() -> MajorVersion {
+        1
+    }
+    fn minor_version() -> MinorVersion {
+        0
+    }
+}
+
+impl ServiceMethods for AtomicCounter {
+    fn register_methods(builder: &mut MethodsBuilder<Self>) {
+        builder.add_shared_method(ADD, |counter, request| {
+            let value: u32 = SomeIpCodec::decode(request.get_payload().get_data())?;
+            let total = counter.total.fetch_add(value, Ordering::SeqCst) + value;
+
+            let mut response = Message::response(request);
+            response.set_payload(&Payload::with_data(&SomeIpCodec::encode(&total)?));
+
+            Ok(Some(response))
+        });
+    }
+    fn on_stop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+fn client(loopback: &Loopback) -> Client {
+    let config = ApplicationConfig {
+        name: "Client".into(),
+        id: 0x2,
+        services: vec![ServiceConfig {
+            id: SERVICE_ID,
+            instance_id: 0x0001,
+            major_version: 1,
+            minor_version: 0,
+            conn_type: ConnectionType::Udp(30509),
+        }],
+    };
+
+    ClientBuilder::with_application(config)
+        .backend(loopback.clone())
+        .call_options(CallOptions::default().timeout(Duration::from_secs(1)))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_request_response() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+
+    assert_eq!(client.send::<u32, u32>(ADD, 5).unwrap(), 5);
+    assert_eq!(client.send::<u32, u32>(ADD, 10).unwrap(), 15);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_error_response() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert_eq!(client.send::<u32, u32>(ADD, 2).unwrap(), 2);
+
+    let err = client.send::<u32, u32>(SUBTRACT, 3).unwrap_err();
+    assert!(matches!(err, SmipError::Remote { code: ReturnCode::Application(0x20), .. }));
+    assert_eq!(err.remote_payload::<SomeIpCodec, u32>().unwrap().unwrap(), 2);
+
+    // The failed call left the service untouched
+    assert_eq!(client.send::<u32, u32>(SUBTRACT, 2).unwrap(), 0);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_method_panic() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert!(matches!(client.send::<(), ()>(EXPLODE, ()), Err(SmipError::Remote { code: ReturnCode::NotOk, .. })));
+
+    // The service keeps serving requests under `PanicPolicy::Continue`
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 1);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_method_panic_stop_offering() {
+    let loopback = Loopback::new();
+
+    let config = RuntimeConfig::new("Test", 0x1, 0x0001)
+        .backend(loopback.clone())
+        .panic_policy(PanicPolicy::StopOffering);
+    let handle = Runtime::new(config)
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert!(matches!(client.send::<(), ()>(EXPLODE, ()), Err(SmipError::Remote { code: ReturnCode::NotOk, .. })));
+
+    // The service is paused in the background
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while !matches!(client.send::<u32, u32>(ADD, 1), Err(SmipError::ServiceUnavailable)) {
+        assert!(Instant::now() < deadline, "service is still offered");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // Resuming offers it again
+    handle.resume(SERVICE_ID, 0x0001).unwrap();
+    assert!(client.send::<u32, u32>(ADD, 1).is_ok());
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_notification() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    let receiver = client.subscribe_iter::<u32>(EVENTGROUP, TOTAL);
+
+    client.send::<u32, u32>(ADD, 3).unwrap();
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), 3);
+
+    drop(receiver);
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_field_subscription() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Thermostat { target: Field::new(21) }, 30509)
+        .start()
+        .unwrap();
+
+    // The field is bound once the service answers
+    let client = client(&loopback);
+    assert_eq!(client.send::<(), u32>(ADD, ()).unwrap(), 21);
+
+    // Only subscribers asking for the field receive its current value
+    let event = client.subscribe_iter::<u32>(EVENTGROUP, TARGET);
+    assert!(event.recv_timeout(Duration::from_millis(100)).is_none());
+    drop(event);
+
+    let field = client.subscribe_field_iter::<u32>(EVENTGROUP, TARGET);
+    assert_eq!(field.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), 21);
+
+    drop(field);
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_shared_subscriptions() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    let first = client.subscribe_iter::<u32>(EVENTGROUP, TOTAL);
+    let second = client.subscribe_iter::<u32>(EVENTGROUP, TOTAL);
+    let panicking = client.subscribe::<u32, _>(EVENTGROUP, TOTAL, |_| panic!("callback panicked"));
+
+    client.send::<u32, u32>(ADD, 3).unwrap();
+    assert_eq!(first.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), 3);
+    assert_eq!(second.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), 3);
+
+    // The event stays subscribed for the other subscriptions
+    drop(first);
+    client.send::<u32, u32>(ADD, 2).unwrap();
+    assert_eq!(second.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), 5);
+
+    drop(second);
+    drop(panicking);
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_unsynchronized() {
+    let loopback = Loopback::new();
+    let counter = AtomicCounter::default();
+    let stopped = counter.stopped.clone();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(counter, 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert_eq!(client.send::<u32, u32>(ADD, 3).unwrap(), 3);
+    assert_eq!(client.send::<u32, u32>(ADD, 4).unwrap(), 7);
+
+    drop(client);
+    handle.shutdown();
+
+    // The service is handed back to `on_stop` once no handler uses it anymore
+    assert!(stopped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_on_stop_panic() {
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(Loopback::new()))
+        .service(Failing, 30510)
+        .start()
+        .unwrap();
+
+    // The panic is logged instead of unwinding out of the shutdown
+    handle.shutdown();
+}
+
+#[test]
+fn test_blocking_in_executor() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+
+    // Blocking calls fail instead of panicking inside another executor
+    let result = futures::executor::block_on(async { client.send::<u32, u32>(ADD, 1) });
+    assert!(matches!(result, Err(SmipError::BlockingInAsync)));
+    assert_eq!(futures::executor::block_on(client.send_async::<u32, u32>(ADD, 1)).unwrap(), 1);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_pause_resume() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 1);
+
+    handle.pause(SERVICE_ID, 0x0001).unwrap();
+    assert!(matches!(client.send::<u32, u32>(ADD, 1), Err(SmipError::ServiceUnavailable)));
+
+    // The service becomes available again with the state it had
+    handle.resume(SERVICE_ID, 0x0001).unwrap();
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 2);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_resume_while_busy() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 1);
+
+    client.fire(HOLD, ()).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+
+    // Offering again binds the events once the method releases the service, without waiting for it
+    let started = Instant::now();
+    handle.pause(SERVICE_ID, 0x0001).unwrap();
+    handle.resume(SERVICE_ID, 0x0001).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(250));
+
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 2);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_remove_add() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 1);
+
+    handle.remove_service(SERVICE_ID, 0x0001).unwrap();
+    assert!(matches!(handle.remove_service(SERVICE_ID, 0x0001), Err(SmipError::ServiceNotRunning(SERVICE_ID, 0x0001))));
+    assert!(matches!(client.send::<u32, u32>(ADD, 1), Err(SmipError::ServiceUnavailable)));
+
+    // Services can only be added as instances configured before the runtime started
+    assert!(matches!(handle.add_service_instance(Counter::default(), 0x0002), Err(SmipError::ServiceNotReserved(SERVICE_ID, 0x0002))));
+
+    handle.add_service(Counter::default()).unwrap();
+    assert!(matches!(handle.add_service(Counter::default()), Err(SmipError::ServiceAlreadyRunning(SERVICE_ID, 0x0001))));
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 1);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_reserve_add() {
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .reserve_service::<Counter>(30509)
+        .start()
+        .unwrap();
+
+    let client = client(&loopback);
+    assert!(matches!(client.send::<u32, u32>(ADD, 1), Err(SmipError::ServiceUnavailable)));
+
+    handle.add_service(Counter::default()).unwrap();
+    assert_eq!(client.send::<u32, u32>(ADD, 1).unwrap(), 1);
+
+    drop(client);
+    handle.shutdown();
+}
+
+#[test]
+fn test_separate_loopbacks() {
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(Loopback::new()))
+        .service(Counter::default(), 30509)
+        .start()
+        .unwrap();
+
+    // The service is offered on another loopback, so it never becomes available
+    let client = client(&Loopback::new());
+    assert!(matches!(client.send::<u32, u32>(ADD, 1), Err(SmipError::ServiceUnavailable)));
+
+    // Retries don't back off past the deadline
+    let options = CallOptions::new()
+        .timeout(Duration::from_millis(50))
+        .retries(3)
+        .backoff(Duration::from_secs(10))
+        .deadline(Instant::now() + Duration::from_millis(300));
+
+    let started = Instant::now();
+    assert!(matches!(client.send_with::<u32, u32>(ADD, 1, &options), Err(SmipError::Timeout)));
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    drop(client);
+    handle.shutdown();
+}
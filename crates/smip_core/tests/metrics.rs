@@ -0,0 +1,111 @@
+//! Metrics recorded by runtimes and clients over the in-process loopback backend.
+//!
+//! Kept apart from the other loopback tests since the recorder is installed for the whole process.
+
+use std::{collections::HashMap, time::Duration};
+
+use futures_timer::Delay;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use smip_core::*;
+use someip_types::*;
+
+const SERVICE_ID: ServiceId = 0x1234;
+const ECHO: MethodId = 0x0001;
+const SLOW: MethodId = 0x0002;
+
+struct Echo;
+
+impl ServiceDefinition for Echo {
+    type Codec = SomeIpCodec;
+
+    fn id() -> ServiceId {
+        SERVICE_ID
+    }
+    fn major_version() -> MajorVersion {
+        1
+    }
+    fn minor_version() -> MinorVersion {
+        0
+    }
+}
+
+impl ServiceMethods for Echo {
+    fn register_methods(builder: &mut MethodsBuilder<Self>) {
+        builder.add_shared_method(ECHO, |_, request| {
+            let mut response = Message::response(request);
+            response.set_payload(&request.get_payload());
+
+            Ok(Some(response))
+        });
+        builder.add_async_method(SLOW, |_, request| Box::pin(async move {
+            Delay::new(Duration::from_millis(200)).await;
+            Ok(Some(Message::response(&request)))
+        }));
+    }
+}
+
+/// Counters recorded since the last snapshot by name, summed over all label sets.
+fn counters(snapshotter: &Snapshotter) -> HashMap<String, u64> {
+    let mut counters = HashMap::new();
+
+    for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+        if let DebugValue::Counter(value) = value {
+            *counters.entry(key.key().name().to_owned()).or_default() += value;
+        }
+    }
+
+    counters
+}
+
+#[test]
+fn test_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install().unwrap();
+
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Echo, 30509)
+        .start()
+        .unwrap();
+
+    let config = ApplicationConfig {
+        name: "Client".into(),
+        id: 0x2,
+        services: vec![ServiceConfig {
+            id: SERVICE_ID,
+            instance_id: 0x0001,
+            major_version: 1,
+            minor_version: 0,
+            conn_type: ConnectionType::Udp(30509),
+        }],
+    };
+    let client = ClientBuilder::with_application(config)
+        .backend(loopback.clone())
+        .call_options(CallOptions::default().timeout(Duration::from_secs(1)))
+        .build()
+        .unwrap();
+
+    assert_eq!(client.send::<u32, u32>(ECHO, 5).unwrap(), 5);
+    let recorded = counters(&snapshotter);
+    assert_eq!(recorded["smip_client_calls_total"], 1);
+    assert_eq!(recorded["smip_requests_total"], 1);
+    assert_eq!(recorded["smip_request_bytes_total"], 4);
+    assert_eq!(recorded["smip_response_bytes_total"], 4);
+
+    // Every attempt that times out is counted, not just the call
+    let options = CallOptions::new()
+        .timeout(Duration::from_millis(50))
+        .retries(2)
+        .backoff(Duration::from_millis(10));
+
+    assert!(matches!(client.send_with::<(), ()>(SLOW, (), &options), Err(SmipError::Timeout)));
+    let recorded = counters(&snapshotter);
+    assert_eq!(recorded["smip_client_calls_total"], 1);
+    assert_eq!(recorded["smip_client_timeouts_total"], 3);
+    assert_eq!(recorded["smip_client_errors_total"], 1);
+
+    drop(client);
+    handle.shutdown();
+}
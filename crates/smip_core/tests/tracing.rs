@@ -0,0 +1,147 @@
+//! Spans recorded by runtimes and clients over the in-process loopback backend.
+//!
+//! Kept apart from the other loopback tests since the subscriber is installed for the whole process.
+
+use std::{collections::HashMap, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use smip_core::*;
+use someip_types::*;
+use tracing::{field::{Field, Visit}, span::{Attributes, Id, Record}, Subscriber};
+use tracing_subscriber::{layer::{Context, SubscriberExt}, Layer, Registry};
+
+const SERVICE_ID: ServiceId = 0x1234;
+const ECHO: MethodId = 0x0001;
+
+struct Echo;
+
+impl ServiceDefinition for Echo {
+    type Codec = SomeIpCodec;
+
+    fn id() -> ServiceId {
+        SERVICE_ID
+    }
+    fn major_version() -> MajorVersion {
+        1
+    }
+    fn minor_version() -> MinorVersion {
+        0
+    }
+}
+
+impl ServiceMethods for Echo {
+    fn register_methods(builder: &mut MethodsBuilder<Self>) {
+        builder.add_shared_method(ECHO, |_, request| {
+            let started = Instant::now();
+
+            let mut response = Message::response(request);
+            response.set_payload(&request.get_payload());
+
+            __private::record_elapsed("handler_us", started);
+
+            Ok(Some(response))
+        });
+    }
+}
+
+/// Name and fields of a span, the fields formatted with their `Debug` representation.
+type RecordedSpan = (&'static str, HashMap<&'static str, String>);
+
+/// Collects the fields of every span once it's closed.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    open: Arc<Mutex<HashMap<Id, RecordedSpan>>>,
+    closed: Arc<Mutex<Vec<RecordedSpan>>>,
+}
+
+impl SpanRecorder {
+    /// Fields of the closed span called `name`, waiting a bit for handlers still winding down.
+    fn fields(&self, name: &str) -> HashMap<&'static str, String> {
+        let deadline = Instant::now() + Duration::from_secs(1);
+
+        loop {
+            let closed = self.closed.lock().unwrap();
+            if let Some((_, fields)) = closed.iter().find(|(span_name, _)| *span_name == name) {
+                return fields.clone();
+            }
+            drop(closed);
+
+            assert!(Instant::now() < deadline, "No {name} span was recorded");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<&'static str, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SpanRecorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+
+        self.open.lock().unwrap().insert(id.clone(), (attrs.metadata().name(), fields));
+    }
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        if let Some((_, fields)) = self.open.lock().unwrap().get_mut(id) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        if let Some(span) = self.open.lock().unwrap().remove(&id) {
+            self.closed.lock().unwrap().push(span);
+        }
+    }
+}
+
+#[test]
+fn test_spans() {
+    let recorder = SpanRecorder::default();
+    tracing::subscriber::set_global_default(Registry::default().with(recorder.clone())).unwrap();
+
+    let loopback = Loopback::new();
+
+    let handle = Runtime::new(RuntimeConfig::new("Test", 0x1, 0x0001).backend(loopback.clone()))
+        .service(Echo, 30509)
+        .start()
+        .unwrap();
+
+    let config = ApplicationConfig {
+        name: "Client".into(),
+        id: 0x2,
+        services: vec![ServiceConfig {
+            id: SERVICE_ID,
+            instance_id: 0x0001,
+            major_version: 1,
+            minor_version: 0,
+            conn_type: ConnectionType::Udp(30509),
+        }],
+    };
+    let client = ClientBuilder::with_application(config)
+        .backend(loopback.clone())
+        .call_options(CallOptions::default().timeout(Duration::from_secs(1)))
+        .build()
+        .unwrap();
+
+    assert_eq!(client.send::<u32, u32>(ECHO, 5).unwrap(), 5);
+
+    drop(client);
+    handle.shutdown();
+
+    let call = recorder.fields("smip.call");
+    assert_eq!(call["service"], SERVICE_ID.to_string());
+    assert_eq!(call["method"], ECHO.to_string());
+    assert_eq!(call["attempts"], "1");
+    assert_eq!(call["return_code"], "Ok");
+
+    let request = recorder.fields("smip.request");
+    assert_eq!(request["service"], SERVICE_ID.to_string());
+    assert_eq!(request["method"], ECHO.to_string());
+    assert_eq!(request["message_type"], "Request");
+    assert_eq!(request["return_code"], "Ok");
+    assert!(request.contains_key("handler_us"));
+}
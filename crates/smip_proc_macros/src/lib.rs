@@ -21,7 +21,7 @@ pub fn service(args: TokenStream, item: TokenStream) -> TokenStream {
         Err(e) => { return TokenStream::from(e.write_errors()); }
     };
 
-    service::expand_service_impl(&struct_def, args).into()
+    service::expand_service_impl(&struct_def, args).unwrap_or_else(|e| e.write_errors()).into()
 }
 
 #[proc_macro_attribute]
@@ -1,8 +1,8 @@
 use proc_macro2::TokenStream;
 use someip_types::MethodId;
 use std::collections::HashMap;
-use syn::{parse_quote, spanned::Spanned, ImplItemFn, Meta, ReturnType, Type};
-use quote::quote;
+use syn::{parse_quote, punctuated::Punctuated, spanned::Spanned, Ident, ImplItemFn, Meta, ReturnType, Token, Type};
+use quote::{format_ident, quote, quote_spanned};
 
 pub fn expand_methods_impl(mut impl_block: syn::ItemImpl) -> syn::Result<TokenStream> {
     check_valid_impl(&impl_block)?;
@@ -11,25 +11,49 @@ pub fn expand_methods_impl(mut impl_block: syn::ItemImpl) -> syn::Result<TokenSt
     
     let mut method_id_to_name = HashMap::new();
 
+    let mut hooks = LifecycleHooks::default();
+
     for item in &mut impl_block.items {
         match item {
             syn::ImplItem::Fn(method) => {
-                if let Some(attr_ix) = extract_method_attr(method) {
+                if let Some((attr_ix, name)) = extract_hook_attr(method) {
+                    check_valid_hook(method)?;
+
+                    method.attrs.remove(attr_ix);
+
+                    let hook = if name == "smip_on_start" {
+                        &mut hooks.on_start
+                    } else {
+                        &mut hooks.on_stop
+                    };
+
+                    if hook.is_some() {
+                        return Err(syn::Error::new(method.sig.ident.span(), format!("only one {} hook is allowed", name)));
+                    }
+
+                    *hook = Some(method.sig.ident.clone());
+                } else if let Some(attr_ix) = extract_method_attr(method) {
                     check_valid_method(method)?;
                     
                     let attribute = method.attrs.remove(attr_ix);
                     method.attrs.push(parse_quote!(#[allow(unused)]));
                     
-                    let method_id = extract_method_id(&attribute.meta)?;
+                    let args = parse_method_args(&attribute.meta)?;
+                    let method_id = args.id;
 
                     if let Some(method_name) = method_id_to_name.get(&method_id) {
                         return Err(syn::Error::new(method.sig.ident.span(), format!("method id {} is already used by method {}", method_id, method_name)));
                     }
 
-                    methods.push((&*method, method_id));
+                    // Nobody receives the result of a fire and forget method
+                    if args.fire_and_forget && matches!(method.sig.output, ReturnType::Type(_, _)) {
+                        return Err(syn::Error::new_spanned(&method.sig.output, "fire_and_forget methods can't return a value"));
+                    }
 
                     method_id_to_name.insert(method_id, method.sig.ident.to_string());
 
+                    methods.push((&*method, args));
+
                 } else {
                     continue;
                 }
@@ -42,12 +66,15 @@ pub fn expand_methods_impl(mut impl_block: syn::ItemImpl) -> syn::Result<TokenSt
 
     let ty = &*impl_block.self_ty;
 
-    let derived_service_methods_impl = derive_service_methods(ty, &methods)?;
+    let derived_service_methods_impl = derive_service_methods(ty, &methods, &hooks)?;
+    let derived_proxy = derive_proxy(ty, &methods)?;
 
     let output = quote!(
         #impl_block
         
         #derived_service_methods_impl
+
+        #derived_proxy
     );
 
     Ok(output)
@@ -91,11 +118,48 @@ fn check_valid_method(method: &ImplItemFn) -> syn::Result<()> {
         return Err(syn::Error::new(method.sig.inputs.span(), "method should have self as the first argument"));
     }
 
-    let n_args = method.sig.inputs.len();
+    Ok(())
+}
+
+/// Types of the arguments after `self`, named after their position so they
+/// can't shadow the variables of the generated code.
+fn method_args(method: &ImplItemFn) -> Vec<(Ident, &Type)> {
+    method.sig.inputs.iter().skip(1).enumerate().filter_map(|(ix, arg)| match arg {
+        syn::FnArg::Typed(arg) => Some((format_ident!("__smip_arg{}", ix), &*arg.ty)),
+        syn::FnArg::Receiver(_) => None,
+    }).collect()
+}
+
+/// Methods marked with `#[smip_on_start]` and `#[smip_on_stop]`.
+#[derive(Default)]
+struct LifecycleHooks {
+    on_start: Option<Ident>,
+    on_stop: Option<Ident>,
+}
 
-    // method should have zero or one arguments other than self
-    if !(n_args == 1 || n_args == 2)  {
-        return Err(syn::Error::new(method.sig.inputs.span(), "method should have exactly one arguments"));
+fn extract_hook_attr(method: &ImplItemFn) -> Option<(usize, &'static str)> {
+    method.attrs.iter().enumerate().find_map(|(ix, attr)| {
+        ["smip_on_start", "smip_on_stop"].into_iter()
+            .find(|name| attr.path().is_ident(name))
+            .map(|name| (ix, name))
+    })
+}
+
+fn check_valid_hook(method: &ImplItemFn) -> syn::Result<()> {
+    if extract_method_attr(method).is_some() {
+        return Err(syn::Error::new(method.sig.ident.span(), "lifecycle hooks can't be methods"));
+    }
+
+    if method.sig.asyncness.is_some() {
+        return Err(syn::Error::new(method.sig.asyncness.span(), "lifecycle hooks can't be async"));
+    }
+
+    if !matches!(method.sig.inputs.first(), Some(syn::FnArg::Receiver(receiver)) if receiver.reference.is_some()) || method.sig.inputs.len() != 1 {
+        return Err(syn::Error::new(method.sig.inputs.span(), "lifecycle hooks should only take &self or &mut self"));
+    }
+
+    if !method.sig.generics.params.is_empty() || !matches!(method.sig.output, ReturnType::Default) {
+        return Err(syn::Error::new(method.sig.ident.span(), "lifecycle hooks can't be generic or return a value"));
     }
 
     Ok(())
@@ -113,89 +177,226 @@ fn extract_method_attr(method: &ImplItemFn) -> Option<usize> {
 
     None
 }
-fn extract_method_id(meta: &Meta) -> syn::Result<MethodId> {
-    match meta {
-        Meta::List(meta) => {
-            let meta: Meta = meta.parse_args()?;
-            match meta {
-                Meta::NameValue(meta) => {
-                    meta.path.get_ident().map_or(Err(syn::Error::new(meta.span(), "expected id")), |ident| {
-                        let id = ident.to_string();
-                        if id != "id" {
-                            return Err(syn::Error::new(meta.span(), "expected id"));
-                        }
-                        
-                        match &meta.value {
-                            syn::Expr::Lit(syn::ExprLit{lit, ..}) => {
-                                match lit {
-                                    syn::Lit::Int(int) => {
-                                        let id = int.base10_parse::<u16>()?;
-                                        Ok(id)
-                                    },
-                                    _ => Err(syn::Error::new(meta.span(), "method id should be a number"))
-                                }
-                            },
-                            _ => return Err(syn::Error::new(meta.span(), "method id should be a number"))
-                        }
-                    })
-                },
-                _ => Err(syn::Error::new(meta.span(), "expected #[smip_method(id = xyzw)] to set method id"))
-            }
+/// Arguments of a `#[smip_method(..)]` attribute.
+pub struct MethodArgs {
+    pub id: MethodId,
+    pub fire_and_forget: bool,
+    /// Called over UDP instead of TCP.
+    pub unreliable: bool,
+}
+
+fn parse_method_args(meta: &Meta) -> syn::Result<MethodArgs> {
+    let Meta::List(list) = meta else {
+        return Err(syn::Error::new(meta.span(), "expected #[smip_method(id = xyzw)] to set method id"));
+    };
+
+    let mut id = None;
+    let mut fire_and_forget = false;
+    let mut unreliable = false;
+
+    for meta in list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+        match meta {
+            Meta::NameValue(meta) => {
+                if !meta.path.is_ident("id") {
+                    return Err(syn::Error::new(meta.span(), "expected id"));
+                }
+
+                match &meta.value {
+                    syn::Expr::Lit(syn::ExprLit{lit: syn::Lit::Int(int), ..}) => {
+                        id = Some(int.base10_parse::<u16>()?);
+                    },
+                    _ => return Err(syn::Error::new(meta.span(), "method id should be a number"))
+                }
+            },
+            Meta::Path(path) if path.is_ident("fire_and_forget") => {
+                fire_and_forget = true;
+            },
+            Meta::Path(path) if path.is_ident("unreliable") => {
+                unreliable = true;
+            },
+            meta => return Err(syn::Error::new(meta.span(), "unknown smip_method argument"))
+        }
+    }
+
+    let id = id.ok_or_else(|| syn::Error::new(meta.span(), "expected #[smip_method(id = xyzw)] to set method id"))?;
+
+    Ok(MethodArgs { id, fire_and_forget, unreliable })
+}
+
+/// Returns `T` if the method returns a `Result<T, E>`.
+///
+/// Only the path is known here, so aliases with a single parameter like
+/// `io::Result<T>` are returned as a plain value, spell out the error type
+/// to send it as an error response.
+fn result_ok_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::Path(path) = &**ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 2 => match args.args.first() {
+            Some(syn::GenericArgument::Type(ty)) => Some(ty),
+            _ => None,
         },
-        _ => Err(syn::Error::new(meta.span(), "expected #[smip_method(id = xyzw)] to set method id"))
+        _ => None,
     }
 }
 
-fn derive_service_methods(service_name: &Type, methods: &[(&ImplItemFn, MethodId)] ) -> syn::Result<TokenStream> {
-    let methods = methods.iter().map(|(method, method_id)| {
-        let method_name = &method.sig.ident;
-        let method_id = *method_id;
-        let return_type = &method.sig.output;
+fn derive_service_methods(service_name: &Type, methods: &[(&ImplItemFn, MethodArgs)], hooks: &LifecycleHooks) -> syn::Result<TokenStream> {
+    // Conflicting implementations reject ids that are also used by a field of the service
+    let method_ids = methods.iter().map(|(method, args)| {
+        let id = args.id;
+        quote_spanned!(method.sig.ident.span()=>
+            impl ::smip::__private::MethodIdUsed<#id> for #service_name {}
+        )
+    }).collect::<Vec<_>>();
 
-        let is_getter = method.sig.inputs.len() == 1;
-        let has_return_type = matches!(return_type, ReturnType::Type(_, _));
+    let methods = methods.iter().map(|(method, args)| {
+        let method_name = &method.sig.ident;
+        let method_id = args.id;
+
+        let inputs = method_args(method);
+        let names = inputs.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let types = inputs.iter().map(|(_, ty)| ty);
+
+        // Several arguments are decoded in order as a tuple, borrowed arguments point into the payload
+        let decode_args = match inputs.len() {
+            0 => quote!(),
+            1 => {
+                let (name, ty) = &inputs[0];
+                quote!(
+                    let payload = message.get_payload();
+                    let #name: #ty = <<#service_name as ::smip::ServiceDefinition>::Codec as ::smip::PayloadCodec>::decode(payload.get_data())?;
+                )
+            },
+            _ => {
+                quote!(
+                    let payload = message.get_payload();
+                    let (#(#names,)*): (#(#types,)*) = <<#service_name as ::smip::ServiceDefinition>::Codec as ::smip::PayloadCodec>::decode(payload.get_data())?;
+                )
+            },
+        };
 
-        let parse_request_payload = quote!(
-            let payload = message.get_payload();
-            let arg = ::smip::FromPayload::from_payload(payload.get_data())?;
+        // Timings are recorded in the request's span
+        let decode_args = quote!(
+            let started = ::std::time::Instant::now();
+            #decode_args
+            ::smip::__private::record_elapsed("decode_us", started);
         );
 
-        let call_method = if is_getter {
-                quote!( 
-                    let output = service.#method_name(); 
-                )
+        let is_async = method.sig.asyncness.is_some();
+        let is_mut = matches!(method.sig.inputs.first(), Some(syn::FnArg::Receiver(receiver)) if receiver.mutability.is_some());
+
+        let call_method = if is_async {
+            // The service stays locked until the method's future completes, `&self` methods share the lock
+            let lock = if is_mut {
+                quote!(write_owned)
+            } else {
+                quote!(read_owned)
+            };
+
+            quote!(
+                #decode_args
+                let mut service = service.#lock().await;
+                let started = ::std::time::Instant::now();
+                let output = service.#method_name(#(#names),*).await;
+                ::smip::__private::record_elapsed("handler_us", started);
+            )
         } else {
-                quote!( 
-                    #parse_request_payload
-                    let output = service.#method_name(arg); 
-                )
+            quote!(
+                #decode_args
+                let started = ::std::time::Instant::now();
+                let output = service.#method_name(#(#names),*);
+                ::smip::__private::record_elapsed("handler_us", started);
+            )
         };
 
         
+        // Errors of methods returning a `Result` are sent as error responses
+        let unwrap_result = if result_ok_type(&method.sig.output).is_some() {
+            quote!(
+                let output = match output {
+                    Ok(output) => output,
+                    Err(err) => {
+                        return ::smip::error_response::<<#service_name as ::smip::ServiceDefinition>::Codec, _>(message, &err).map(Some);
+                    }
+                };
+            )
+        } else {
+            quote!()
+        };
+
         let write_response_payload = quote!(
-            let result_payload = ::smip::ToPayload::to_payload(&output)?;
+            let started = ::std::time::Instant::now();
+            let result_payload = <<#service_name as ::smip::ServiceDefinition>::Codec as ::smip::PayloadCodec>::encode(&output)?;
+            ::smip::__private::record_elapsed("encode_us", started);
 
             let mut response = ::smip::Message::response(message);
             response.set_payload(&::smip::Payload::with_data(&result_payload));
 
         );
 
-        let return_message = if has_return_type {
+        // Methods without a return type still confirm the call with an empty response
+        let return_message = if args.fire_and_forget {
+            quote!(
+                ::std::result::Result::<_, ::smip::SmipError>::Ok(None)
+            )
+        } else {
             quote!(
                 #write_response_payload
-                Ok(Some(response))
-            ) 
+                ::std::result::Result::<_, ::smip::SmipError>::Ok(Some(response))
+            )
+        };
+
+        // Unsynchronized services can't hand out `&mut self`
+        let check_concurrency = if is_mut {
+            quote_spanned!(method.sig.inputs.span()=>
+                {
+                    fn assert_synchronized<S: ::smip::Synchronized>() {}
+                    let _ = assert_synchronized::<Self>;
+                }
+            )
+        } else {
+            quote!()
+        };
+
+        let add_method = if is_async {
+            quote!(
+                builder.add_async_method(#method_id, |service, message| ::std::boxed::Box::pin(async move {
+                    let message = &message;
+
+                    #call_method
+                    #unwrap_result
+                    #return_message
+                }));
+            )
         } else {
+            let add = if is_mut {
+                quote!(add_method)
+            } else {
+                quote!(add_shared_method)
+            };
+
             quote!(
-                Ok(None)
+                builder.#add(#method_id, |service, message| {
+                    #call_method
+                    #unwrap_result
+                    #return_message
+                });
             )
         };
 
         quote!(
-            builder.add_method(#method_id, |service, message| {   
-                #call_method
-                #return_message
-            });
+            #check_concurrency
+            #add_method
         )
     });
 
@@ -205,13 +406,100 @@ fn derive_service_methods(service_name: &Type, methods: &[(&ImplItemFn, MethodId
         stream.extend(method);
     }
 
+    let on_start = hooks.on_start.as_ref().map(|hook| quote!(
+        fn on_start(&mut self) {
+            #service_name::#hook(self)
+        }
+    ));
+    let on_stop = hooks.on_stop.as_ref().map(|hook| quote!(
+        fn on_stop(&mut self) {
+            #service_name::#hook(self)
+        }
+    ));
+
     Ok(
         quote!(
             impl ::smip::ServiceMethods for #service_name {
                 fn register_methods(builder: &mut ::smip::MethodsBuilder<#service_name>) {
+                    <#service_name as ::smip::ServiceDefinition>::register_fields(builder);
+
                     #stream
                 }
+                #on_start
+                #on_stop
+            }
+
+            #(#method_ids)*
+        )
+    )
+}
+
+fn derive_proxy(service_name: &Type, methods: &[(&ImplItemFn, MethodArgs)]) -> syn::Result<TokenStream> {
+    let service_ident = match service_name {
+        Type::Path(path) => path.path.segments.last().map(|segment| &segment.ident),
+        _ => None
+    }.ok_or_else(|| syn::Error::new(service_name.span(), "expected a named type in methods_impl"))?;
+
+    // Declared by `#[service]` with the visibility of the service
+    let proxy_name = format_ident!("{}Proxy", service_ident);
+
+    let methods = methods.iter().map(|(method, args)| {
+        let method_name = &method.sig.ident;
+        let method_id = args.id;
+
+        let (send, send_with) = if args.fire_and_forget {
+            (quote!(fire), quote!(fire_with))
+        } else {
+            (quote!(send), quote!(send_with))
+        };
+
+        // Errors of `Result` methods arrive as `SmipError::Remote`
+        let output = match (&method.sig.output, result_ok_type(&method.sig.output)) {
+            (_, Some(ty)) => quote!(#ty),
+            (ReturnType::Type(_, ty), None) => quote!(#ty),
+            (ReturnType::Default, None) => quote!(()),
+        };
+
+        let method_name_with = format_ident!("{}_with", method_name);
+
+        let inputs = method_args(method);
+        let params = inputs.iter().map(|(name, ty)| quote!(#name: #ty)).collect::<Vec<_>>();
+        let names = inputs.iter().map(|(name, _)| name);
+
+        let data = match inputs.len() {
+            0 => quote!(()),
+            1 => quote!(#(#names)*),
+            _ => quote!((#(#names,)*)),
+        };
+
+        // Unreliable methods are always called over UDP, regardless of the options
+        if args.unreliable {
+            quote!(
+                pub fn #method_name(&self, #(#params),*) -> ::std::result::Result<#output, ::smip::SmipError> {
+                    self.client.#send_with(#method_id, #data, &self.client.options().reliable(false))
+                }
+                pub fn #method_name_with(&self, #(#params,)* __smip_options: &::smip::CallOptions) -> ::std::result::Result<#output, ::smip::SmipError> {
+                    self.client.#send_with(#method_id, #data, &__smip_options.reliable(false))
+                }
+            )
+        } else {
+            quote!(
+                pub fn #method_name(&self, #(#params),*) -> ::std::result::Result<#output, ::smip::SmipError> {
+                    self.client.#send(#method_id, #data)
+                }
+                pub fn #method_name_with(&self, #(#params,)* __smip_options: &::smip::CallOptions) -> ::std::result::Result<#output, ::smip::SmipError> {
+                    self.client.#send_with(#method_id, #data, __smip_options)
+                }
+            )
+        }
+    });
+
+    Ok(
+        quote!(
+            #[allow(dead_code)]
+            impl #proxy_name {
+                #(#methods)*
             }
         )
     )
-}
\ No newline at end of file
+}
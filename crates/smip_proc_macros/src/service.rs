@@ -1,28 +1,166 @@
 use darling::FromMeta;
-use proc_macro2::TokenStream;
-use someip_types::{MajorVersion, MinorVersion};
-use syn::ItemStruct;
-use quote::quote;
+use proc_macro2::{Span, TokenStream};
+use someip_types::{EventGroupId, EventId, MajorVersion, MethodId, MinorVersion};
+use std::collections::HashMap;
+use syn::{spanned::Spanned, Attribute, Index, ItemStruct, Member};
+use quote::{format_ident, quote, quote_spanned};
 
 #[derive(Debug, FromMeta)]
 pub struct ServiceArgs {
     pub id: u16,
     pub major_version: Option<MajorVersion>,
-    pub minor_version: Option<MinorVersion>
+    pub minor_version: Option<MinorVersion>,
+    pub codec: Option<syn::Path>,
+    pub concurrency: Option<Concurrency>,
 }
 
-pub fn expand_service_impl(struct_def: &ItemStruct, args: ServiceArgs) -> TokenStream {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromMeta)]
+pub enum Concurrency {
+    #[darling(rename = "read_write")]
+    ReadWrite,
+    #[darling(rename = "unsynchronized")]
+    Unsynchronized,
+}
+
+#[derive(Debug, FromMeta)]
+struct EventArgs {
+    id: EventId,
+    eventgroup: EventGroupId,
+}
+
+#[derive(Debug, FromMeta)]
+struct FieldArgs {
+    getter: Option<MethodId>,
+    setter: Option<MethodId>,
+    notifier: Option<EventId>,
+    eventgroup: Option<EventGroupId>,
+}
+
+struct EventMember {
+    member: Member,
+    id: EventId,
+    eventgroup: EventGroupId,
+    is_field: bool,
+}
+
+struct FieldMember {
+    member: Member,
+    getter: Option<MethodId>,
+    setter: Option<MethodId>,
+}
+
+#[derive(Default)]
+struct ServiceMembers {
+    events: Vec<EventMember>,
+    fields: Vec<FieldMember>,
+    event_id_to_member: HashMap<EventId, String>,
+    method_id_to_member: HashMap<MethodId, String>,
+    /// Getter and setter ids with the attribute declaring them.
+    method_ids: Vec<(MethodId, Span)>,
+}
+
+impl ServiceMembers {
+    fn add_event(&mut self, attribute: &Attribute, event: EventMember) -> darling::Result<()> {
+        // Event ids have the most significant bit set to tell them apart from method ids
+        if event.id & 0x8000 == 0 {
+            return Err(darling::Error::custom("event id should be in the range 0x8000..=0xFFFF").with_span(&attribute.meta));
+        }
+
+        let member = &event.member;
+        if let Some(other) = self.event_id_to_member.insert(event.id, quote!(#member).to_string()) {
+            return Err(darling::Error::custom(format!("event id {} is already used by {}", event.id, other)).with_span(&attribute.meta));
+        }
+
+        self.events.push(event);
+
+        Ok(())
+    }
+    fn add_field_method(&mut self, attribute: &Attribute, member: &Member, id: MethodId) -> darling::Result<()> {
+        if id & 0x8000 != 0 {
+            return Err(darling::Error::custom("getter and setter ids should be in the range 0x0000..=0x7FFF").with_span(&attribute.meta));
+        }
+
+        if let Some(other) = self.method_id_to_member.insert(id, quote!(#member).to_string()) {
+            return Err(darling::Error::custom(format!("method id {} is already used by {}", id, other)).with_span(&attribute.meta));
+        }
+
+        self.method_ids.push((id, attribute.meta.span()));
+
+        Ok(())
+    }
+}
+
+pub fn expand_service_impl(struct_def: &ItemStruct, args: ServiceArgs) -> darling::Result<TokenStream> {
+    let mut struct_def = struct_def.clone();
     let struct_name = struct_def.ident.clone();
 
     let id = args.id;
     let major_version = args.major_version.unwrap_or(MajorVersion::default());
     let minor_version = args.minor_version.unwrap_or(MinorVersion::default());
+    let codec = args.codec.map_or(quote!(::smip::SomeIpCodec), |codec| quote!(#codec));
 
-    quote!(
-        #struct_def 
+    let concurrency = args.concurrency.unwrap_or(Concurrency::ReadWrite);
+
+    let members = extract_members(&mut struct_def, concurrency)?;
+
+    let event_definitions = members.events.iter().map(|event| {
+        let id = event.id;
+        let eventgroup = event.eventgroup;
+        let event_type = if event.is_field {
+            quote!(::smip::EventType::Field)
+        } else {
+            quote!(::smip::EventType::Event)
+        };
+
+        quote!(
+            ::smip::EventDefinition {
+                id: #id,
+                eventgroup: #eventgroup,
+                event_type: #event_type,
+            }
+        )
+    });
+
+    let event_bindings = members.events.iter().map(|event| {
+        let member = &event.member;
+        let id = event.id;
+
+        quote!(
+            self.#member.bind(binder, #id);
+        )
+    });
+
+    let field_methods = members.fields.iter().map(derive_field_methods);
+
+    let concurrency_variant = match concurrency {
+        Concurrency::ReadWrite => quote!(::smip::Concurrency::ReadWrite),
+        Concurrency::Unsynchronized => quote!(::smip::Concurrency::Unsynchronized),
+    };
+
+    // Methods of `#[methods_impl]` mark their ids the same way, so a field can't reuse them
+    let method_ids = members.method_ids.iter().map(|(id, span)| quote_spanned!(*span=>
+        impl ::smip::__private::MethodIdUsed<#id> for #struct_name {}
+    ));
+
+    // The methods are added by `#[methods_impl]`, the struct is declared here to share the service's visibility
+    let vis = &struct_def.vis;
+    let proxy_name = format_ident!("{}Proxy", struct_name);
+    let proxy_doc = format!("Typed client for the methods of [`{}`].", struct_name);
+
+    let synchronized = (concurrency == Concurrency::ReadWrite).then(|| quote!(
+        #[automatically_derived]
+        impl ::smip::Synchronized for #struct_name {}
+    ));
+
+    Ok(quote!(
+        #struct_def
 
         #[automatically_derived]
         impl ::smip::ServiceDefinition for #struct_name {
+            type Codec = #codec;
+
+            const CONCURRENCY: ::smip::Concurrency = #concurrency_variant;
+
             fn id() -> ::smip::ServiceId {
                 #id
             }
@@ -32,6 +170,146 @@ pub fn expand_service_impl(struct_def: &ItemStruct, args: ServiceArgs) -> TokenS
             fn minor_version() -> ::smip::MinorVersion {
                 #minor_version
             }
+            fn events() -> ::std::vec::Vec<::smip::EventDefinition> {
+                ::std::vec![#(#event_definitions),*]
+            }
+            fn bind_events(&self, binder: &::smip::EventBinder<Self>) {
+                #(#event_bindings)*
+            }
+            fn register_fields(builder: &mut ::smip::MethodsBuilder<Self>) {
+                #(#field_methods)*
+            }
+        }
+
+        #[doc = #proxy_doc]
+        #[allow(dead_code)]
+        #vis struct #proxy_name {
+            client: ::smip::Client<#codec>,
+        }
+
+        #[allow(dead_code)]
+        impl #proxy_name {
+            pub fn new(client: ::smip::Client<#codec>) -> Self {
+                assert_eq!(
+                    client.service_id(),
+                    <#struct_name as ::smip::ServiceDefinition>::id(),
+                    "client is configured for a different service"
+                );
+
+                Self { client }
+            }
+            pub fn client(&self) -> &::smip::Client<#codec> {
+                &self.client
+            }
         }
+
+        #synchronized
+        #(#method_ids)*
+    ))
+}
+
+fn extract_members(struct_def: &mut ItemStruct, concurrency: Concurrency) -> darling::Result<ServiceMembers> {
+    let mut members = ServiceMembers::default();
+
+    for (ix, field) in struct_def.fields.iter_mut().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(ix)),
+        };
+
+        if let Some(attr_ix) = field.attrs.iter().position(|attr| attr.path().is_ident("smip_event")) {
+            let attribute = field.attrs.remove(attr_ix);
+            let args = EventArgs::from_meta(&attribute.meta).map_err(|err| err.with_span(&attribute.meta))?;
+
+            members.add_event(&attribute, EventMember {
+                member: member.clone(),
+                id: args.id,
+                eventgroup: args.eventgroup,
+                is_field: false,
+            })?;
+        }
+
+        if let Some(attr_ix) = field.attrs.iter().position(|attr| attr.path().is_ident("smip_field")) {
+            let attribute = field.attrs.remove(attr_ix);
+            let args = FieldArgs::from_meta(&attribute.meta).map_err(|err| err.with_span(&attribute.meta))?;
+
+            if args.getter.is_none() && args.setter.is_none() && args.notifier.is_none() {
+                return Err(darling::Error::custom("field should have at least one of getter, setter or notifier").with_span(&attribute.meta));
+            }
+
+            // Setting a field needs exclusive access to the service
+            if args.setter.is_some() && concurrency == Concurrency::Unsynchronized {
+                return Err(darling::Error::custom("field setters are not supported in unsynchronized services").with_span(&attribute.meta));
+            }
+
+            match (args.notifier, args.eventgroup) {
+                (Some(id), Some(eventgroup)) => {
+                    members.add_event(&attribute, EventMember {
+                        member: member.clone(),
+                        id,
+                        eventgroup,
+                        is_field: true,
+                    })?;
+                },
+                (Some(_), None) => {
+                    return Err(darling::Error::custom("field notifier requires an eventgroup").with_span(&attribute.meta));
+                },
+                (None, Some(_)) => {
+                    return Err(darling::Error::custom("eventgroup is only allowed together with a notifier").with_span(&attribute.meta));
+                },
+                (None, None) => {}
+            }
+
+            for id in args.getter.iter().chain(args.setter.iter()) {
+                members.add_field_method(&attribute, &member, *id)?;
+            }
+
+            members.fields.push(FieldMember {
+                member,
+                getter: args.getter,
+                setter: args.setter,
+            });
+        }
+    }
+
+    Ok(members)
+}
+
+fn derive_field_methods(field: &FieldMember) -> TokenStream {
+    let member = &field.member;
+
+    let write_response_payload = quote!(
+        let result_payload = <<Self as ::smip::ServiceDefinition>::Codec as ::smip::PayloadCodec>::encode(service.#member.get())?;
+
+        let mut response = ::smip::Message::response(message);
+        response.set_payload(&::smip::Payload::with_data(&result_payload));
+    );
+
+    let getter = field.getter.map(|id| {
+        quote!(
+            builder.add_shared_method(#id, |service, message| {
+                #write_response_payload
+                Ok(Some(response))
+            });
+        )
+    });
+
+    let setter = field.setter.map(|id| {
+        quote!(
+            builder.add_method(#id, |service, message| {
+                let payload = message.get_payload();
+                let value = <<Self as ::smip::ServiceDefinition>::Codec as ::smip::PayloadCodec>::decode(payload.get_data())?;
+
+                service.#member.set(value)?;
+
+                #write_response_payload
+                Ok(Some(response))
+            });
+        )
+    });
+
+    quote!(
+        #getter
+        #setter
     )
-}
\ No newline at end of file
+}
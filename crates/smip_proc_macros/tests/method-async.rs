@@ -0,0 +1,61 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Store {
+    value: u32,
+}
+
+async fn lookup(key: &str) -> u32 {
+    key.len() as u32
+}
+
+#[derive(Clone, Copy)]
+struct Busy;
+
+impl From<Busy> for ReturnCode {
+    fn from(_: Busy) -> Self {
+        ReturnCode::NotReady
+    }
+}
+
+#[methods_impl]
+impl Store {
+    #[smip_method(id = 1)]
+    async fn get(&self, key: &str) -> u32 {
+        self.value + lookup(key).await
+    }
+    #[smip_method(id = 2)]
+    async fn set(&mut self, value: u32) {
+        self.value = value;
+    }
+    #[smip_method(id = 3)]
+    async fn try_set(&mut self, value: u32, force: bool) -> Result<u32, Busy> {
+        if self.value != 0 && !force {
+            return Err(Busy);
+        }
+        self.value = value;
+        Ok(value)
+    }
+    #[smip_method(id = 4, fire_and_forget)]
+    async fn log(&self, line: String) {
+        let _ = line;
+    }
+    #[smip_method(id = 5)]
+    fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+fn call(proxy: &StoreProxy) -> Result<u32, SmipError> {
+    proxy.set(1)?;
+    proxy.try_set(2, true)?;
+    proxy.log("hello".to_string())?;
+    proxy.get("key")
+}
+
+fn main() {
+    let runtime = Runtime::new(RuntimeConfig::new("Store", 0x1, 0x1))
+        .service(Store { value: 0 }, 30509);
+
+    let _ = (runtime, call);
+}
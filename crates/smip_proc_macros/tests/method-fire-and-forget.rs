@@ -0,0 +1,27 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Logger {
+    lines: Vec<String>,
+}
+
+#[methods_impl]
+impl Logger {
+    #[smip_method(id = 1, fire_and_forget)]
+    fn log(&mut self, line: String) {
+        self.lines.push(line);
+    }
+    #[smip_method(id = 2)]
+    fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+fn call(proxy: &LoggerProxy) -> Result<(), SmipError> {
+    proxy.log("started".to_string())?;
+    proxy.clear()
+}
+
+fn main() {
+    let _ = call;
+}
@@ -0,0 +1,37 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Service {
+    lines: Vec<String>,
+}
+
+#[methods_impl]
+impl Service {
+    #[smip_method(id = 1)]
+    fn add(&self, a: u32, b: u32) -> u32 {
+        a + b
+    }
+    #[smip_method(id = 2)]
+    fn append(&mut self, prefix: &str, line: &str, (tag, count): (u8, u16)) -> usize {
+        for _ in 0..count {
+            self.lines.push(format!("{}{} {}", prefix, tag, line));
+        }
+        self.lines.len()
+    }
+    #[smip_method(id = 3)]
+    fn checksum(&self, data: &[u8]) -> u8 {
+        data.iter().fold(0, |acc, byte| acc ^ byte)
+    }
+}
+
+fn call(proxy: &ServiceProxy) -> Result<usize, SmipError> {
+    let options = CallOptions::new().retries(1);
+
+    proxy.add(1, 2)?;
+    proxy.checksum(&[1, 2, 3])?;
+    proxy.append_with("> ", "hello", (1, 2), &options)
+}
+
+fn main() {
+    let _ = call;
+}
@@ -0,0 +1,30 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Settings {
+    value: u32,
+}
+
+#[methods_impl]
+impl Settings {
+    #[smip_method(id = 1)]
+    fn set(&mut self, options: u32, message: u32) -> u32 {
+        self.value = options + message;
+        self.value
+    }
+    #[smip_method(id = 2, unreliable)]
+    fn apply(&mut self, client: u32, (a, b): (u32, u32)) {
+        self.value = client + a + b;
+    }
+}
+
+fn call(proxy: &SettingsProxy) -> Result<u32, SmipError> {
+    let options = CallOptions::new().retries(2);
+
+    proxy.apply_with(1, (2, 3), &options)?;
+    proxy.set_with(1, 2, &options)
+}
+
+fn main() {
+    let _ = call;
+}
@@ -0,0 +1,37 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Counter {
+    value: u32,
+}
+
+#[methods_impl]
+impl Counter {
+    #[smip_method(id = 1)]
+    fn add(&mut self, value: u32) -> u32 {
+        self.value += value;
+        self.value
+    }
+    #[smip_method(id = 2)]
+    fn get(&self) -> u32 {
+        self.value
+    }
+    #[smip_method(id = 3)]
+    fn reset(&mut self) {
+        self.value = 0;
+    }
+}
+
+fn call(proxy: &CounterProxy) -> Result<u32, SmipError> {
+    let options = CallOptions::new()
+        .timeout(std::time::Duration::from_millis(500))
+        .retries(2);
+
+    proxy.reset()?;
+    proxy.add_with(2, &options)?;
+    proxy.get_with(&options)
+}
+
+fn main() {
+    let _ = call;
+}
@@ -0,0 +1,67 @@
+use serde::Serialize;
+use smip::*;
+
+#[derive(Debug, Clone, Copy)]
+enum CounterError {
+    Overflow,
+}
+
+impl From<CounterError> for ReturnCode {
+    fn from(_: CounterError) -> Self {
+        ReturnCode::Application(0x20)
+    }
+}
+
+mod alias {
+    pub type Result<T> = std::result::Result<T, String>;
+}
+
+#[derive(Debug, Serialize)]
+struct Rejected {
+    reason: String,
+}
+
+impl SmipMethodError for Rejected {
+    fn return_code(&self) -> ReturnCode {
+        ReturnCode::NotOk
+    }
+    fn error_payload<C: PayloadCodec>(&self) -> Result<Option<Vec<u8>>, SmipError> {
+        C::encode(self).map(Some)
+    }
+}
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Counter {
+    value: u8,
+}
+
+#[methods_impl]
+impl Counter {
+    #[smip_method(id = 1)]
+    fn add(&mut self, value: u8) -> Result<u8, CounterError> {
+        self.value = self.value.checked_add(value).ok_or(CounterError::Overflow)?;
+        Ok(self.value)
+    }
+    #[smip_method(id = 2)]
+    fn reset(&mut self) -> std::result::Result<(), Rejected> {
+        Err(Rejected { reason: "not allowed".to_string() })
+    }
+    // Single parameter aliases are sent as a plain value
+    #[smip_method(id = 3)]
+    fn check(&self) -> alias::Result<u8> {
+        Ok(self.value)
+    }
+}
+
+fn call(proxy: &CounterProxy) -> Result<u8, SmipError> {
+    if let Err(err @ SmipError::Remote { .. }) = proxy.reset() {
+        let _reason = err.remote_payload::<SomeIpCodec, String>();
+    }
+
+    let _checked: alias::Result<u8> = proxy.check()?;
+    proxy.add(1)
+}
+
+fn main() {
+    let _ = call;
+}
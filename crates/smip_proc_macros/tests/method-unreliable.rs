@@ -0,0 +1,30 @@
+use smip::*;
+
+#[service(id = 0x1234)]
+struct Telemetry {
+    samples: Vec<u16>,
+}
+
+#[methods_impl]
+impl Telemetry {
+    #[smip_method(id = 1, unreliable)]
+    fn latest(&self) -> Option<u16> {
+        self.samples.last().copied()
+    }
+    #[smip_method(id = 2, unreliable, fire_and_forget)]
+    fn push(&mut self, sample: u16) {
+        self.samples.push(sample);
+    }
+}
+
+fn call(proxy: &TelemetryProxy) -> Result<Option<u16>, SmipError> {
+    proxy.push(1)?;
+    proxy.latest_with(&CallOptions::new().retries(2))
+}
+
+fn main() {
+    let runtime = Runtime::new(RuntimeConfig::new("Telemetry", 0x1, 0x1))
+        .service(Telemetry { samples: vec![] }, ConnectionType::Both { tcp: 30509, udp: 30510 });
+
+    let _ = (runtime, call);
+}
@@ -0,0 +1,36 @@
+use smip::*;
+
+#[service(id = 0x1234, codec = BincodeCodec)]
+struct Legacy {
+    #[smip_field(getter = 1, notifier = 0x8001, eventgroup = 1)]
+    value: Field<u32>,
+}
+
+#[methods_impl]
+impl Legacy {
+    #[smip_method(id = 2)]
+    fn add(&mut self, value: u32) -> u32 {
+        let sum = *self.value.get() + value;
+        let _ = self.value.set(sum);
+        sum
+    }
+}
+
+#[service(id = 0x1235, codec = smip::RawCodec)]
+struct Echo;
+
+#[methods_impl]
+impl Echo {
+    #[smip_method(id = 1)]
+    fn echo(&self, data: Vec<u8>) -> Vec<u8> {
+        data
+    }
+}
+
+fn main() {
+    let legacy = <Legacy as ServiceDefinition>::Codec::encode(&1u32).unwrap();
+    assert_eq!(legacy, BincodeCodec::encode(&1u32).unwrap());
+
+    let raw = <Echo as ServiceDefinition>::Codec::encode(&vec![1u8, 2]).unwrap();
+    assert_eq!(raw, [1, 2]);
+}
@@ -0,0 +1,19 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Service {
+    #[smip_event(id = 0x8001, eventgroup = 1)]
+    updated: Event<u32>,
+}
+
+#[methods_impl]
+impl Service {
+    #[smip_method(id = 1)]
+    fn update(&mut self, value: u32) {
+        let _ = self.updated.notify(&value);
+    }
+}
+
+fn main() {
+    assert_eq!(Service::events().len(), 1);
+}
\ No newline at end of file
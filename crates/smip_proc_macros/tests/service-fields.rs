@@ -0,0 +1,24 @@
+use smip::*;
+
+#[service(id = 0x1234, major_version = 1, minor_version = 0)]
+struct Service {
+    #[smip_field(getter = 1, setter = 2, notifier = 0x8001, eventgroup = 1)]
+    value: Field<u32>,
+    #[smip_field(getter = 3)]
+    name: Field<String>,
+}
+
+#[methods_impl]
+impl Service {
+    #[smip_method(id = 4)]
+    fn reset(&mut self) {
+        let _ = self.value.set(0);
+    }
+}
+
+fn main() {
+    let events = Service::events();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, EventType::Field);
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use smip::*;
+
+#[service(id = 0x1234)]
+struct Sensor {
+    running: bool,
+}
+
+#[methods_impl]
+impl Sensor {
+    #[smip_on_start]
+    fn start(&mut self) {
+        self.running = true;
+    }
+    #[smip_on_stop]
+    fn stop(&mut self) {
+        self.running = false;
+    }
+    #[smip_method(id = 1)]
+    fn running(&self) -> bool {
+        self.running
+    }
+}
+
+fn main() {
+    let mut sensor = Sensor { running: false };
+
+    ServiceMethods::on_start(&mut sensor);
+    assert!(sensor.running);
+
+    ServiceMethods::on_stop(&mut sensor);
+    assert!(!sensor.running);
+}
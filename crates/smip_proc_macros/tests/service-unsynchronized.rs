@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use smip::*;
+
+#[service(id = 0x1234, concurrency = "unsynchronized")]
+struct Counter {
+    value: AtomicU32,
+    #[smip_field(getter = 0x10, notifier = 0x8001, eventgroup = 0x0001)]
+    limit: Field<u32>,
+}
+
+#[methods_impl]
+impl Counter {
+    #[smip_method(id = 1)]
+    fn add(&self, value: u32) -> u32 {
+        self.value.fetch_add(value, Ordering::Relaxed) + value
+    }
+    #[smip_method(id = 2)]
+    async fn get(&self) -> u32 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+const _: () = assert!(matches!(<Counter as ServiceDefinition>::CONCURRENCY, Concurrency::Unsynchronized));
+
+fn main() {
+    let _ = Counter { value: AtomicU32::new(0), limit: Field::new(10) };
+}
@@ -0,0 +1,9 @@
+use smip::*;
+
+#[service(id = 0x1234)]
+struct Service {
+    #[smip_event(id = 0x0001, eventgroup = 1)]
+    updated: Event<u32>,
+}
+
+fn main() {}
\ No newline at end of file
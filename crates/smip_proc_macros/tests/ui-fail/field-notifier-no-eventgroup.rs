@@ -0,0 +1,9 @@
+use smip::*;
+
+#[service(id = 0x1234)]
+struct Service {
+    #[smip_field(getter = 1, notifier = 0x8001)]
+    value: Field<u32>,
+}
+
+fn main() {}
\ No newline at end of file
@@ -0,0 +1,9 @@
+use smip::*;
+
+#[service(id = 0x1234, concurrency = "unsynchronized")]
+struct Service {
+    #[smip_field(getter = 0x10, setter = 0x11)]
+    value: Field<u32>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use smip::*;
+
+#[service(id = 0x1234)]
+struct Service {
+    #[smip_field(getter = 0x10, setter = 0x11)]
+    limit: Field<u32>,
+}
+
+#[methods_impl]
+impl Service {
+    #[smip_method(id = 0x11)]
+    fn reset(&mut self) {}
+}
+
+fn main() {}
@@ -5,9 +5,10 @@ struct Service;
 
 #[methods_impl]
 impl Service {
-    #[smip_method(id = 1)]
-    fn method_with_more_than_one_arg(&self, arg1: u32, arg2: String) {}
+    #[smip_method(id = 1, fire_and_forget)]
+    fn foo(&self) -> u32 {
+        0
+    }
 }
 
-
-fn main() {}
\ No newline at end of file
+fn main() {}
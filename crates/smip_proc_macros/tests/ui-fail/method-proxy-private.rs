@@ -0,0 +1,28 @@
+mod dashboard {
+    use smip::*;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Reading(u32);
+
+    #[service(id = 0x1234, major_version = 1, minor_version = 0)]
+    struct Dashboard {
+        reading: u32,
+    }
+
+    #[methods_impl]
+    impl Dashboard {
+        #[smip_method(id = 1)]
+        fn read(&self) -> Reading {
+            Reading(self.reading)
+        }
+    }
+}
+
+// The proxy is as private as the service
+fn call(proxy: &dashboard::DashboardProxy) {
+    let _ = proxy;
+}
+
+fn main() {
+    let _ = call;
+}
@@ -0,0 +1,12 @@
+use smip::*;
+
+#[service(id = 0x1234)]
+struct Service;
+
+#[methods_impl]
+impl Service {
+    #[smip_on_start]
+    fn start(&mut self, delay: u32) {}
+}
+
+fn main() {}
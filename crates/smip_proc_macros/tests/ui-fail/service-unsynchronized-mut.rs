@@ -0,0 +1,16 @@
+use smip::*;
+
+#[service(id = 0x1234, concurrency = "unsynchronized")]
+struct Service {
+    value: u32,
+}
+
+#[methods_impl]
+impl Service {
+    #[smip_method(id = 1)]
+    fn set(&mut self, value: u32) {
+        self.value = value;
+    }
+}
+
+fn main() {}
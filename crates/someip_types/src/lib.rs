@@ -1,18 +1,110 @@
-pub type ServiceId = vsomeip_rs::ServiceId;
-pub type InstanceId = vsomeip_rs::InstanceId;
-pub type MethodId = vsomeip_rs::MethodId;
+pub type ServiceId = u16;
+pub type InstanceId = u16;
+pub type MethodId = u16;
+pub type EventId = u16;
+pub type EventGroupId = u16;
 
 pub type Length = u32;
 
-pub type RequestId = vsomeip_rs::RequestId;
-pub type ClientId = vsomeip_rs::ClientId;
-pub type SessionId = vsomeip_rs::SessionId;
+pub type RequestId = u32;
+pub type ClientId = u16;
+pub type SessionId = u16;
 
-pub type ProtocolVersion = vsomeip_rs::ProtocolVersion;
-pub type InterfaceVersion = vsomeip_rs::InterfaceVersion;
-pub type MajorVersion = vsomeip_rs::MajorVersion;
-pub type MinorVersion = vsomeip_rs::MinorVersion;
+pub type ProtocolVersion = u8;
+pub type InterfaceVersion = u8;
+pub type MajorVersion = u8;
+pub type MinorVersion = u32;
 pub type Port = u16;
 
-pub type ReturnCode = vsomeip_rs::ReturnCode;
-pub type MessageType = vsomeip_rs::MessageType;
\ No newline at end of file
+/// The return code of a SOME/IP message.
+/// SIP_RPC_371
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReturnCode {
+    Ok,
+    NotOk,
+    UnknownService,
+    UnknownMethod,
+    NotReady,
+    NotReachable,
+    Timeout,
+    WrongProtocolVersion,
+    WrongInterfaceVersion,
+    MalformedMessage,
+    WrongMessageType,
+    /// Service or method specific error, the code is in the range
+    /// `0x20..=0x5E` reserved for applications.
+    Application(u8),
+    Unknown,
+}
+
+/// The type of a SOME/IP message.
+/// SIP_RPC_684
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageType {
+    Request,
+    RequestNoReturn,
+    Notification,
+    RequestAck,
+    RequestNoReturnAck,
+    NotificationAck,
+    Response,
+    Error,
+    ResponseAck,
+    ErrorAck,
+    Unknown,
+}
+
+/// The type of an event offered or requested by an application.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventType {
+    #[default]
+    Event,
+    SelectiveEvent,
+    Field,
+}
+
+/// Whether an application is registered, services can only be offered while it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum State {
+    Registered,
+    Deregistered
+}
+
+/// Endpoint a service is offered on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConnectionType {
+    Tcp(Port),
+    Udp(Port),
+    /// Offers the service over TCP and UDP at the same time.
+    Both {
+        tcp: Port,
+        udp: Port,
+    },
+}
+
+impl ConnectionType {
+    pub fn tcp_port(&self) -> Option<Port> {
+        match self {
+            ConnectionType::Tcp(port) | ConnectionType::Both { tcp: port, .. } => Some(*port),
+            ConnectionType::Udp(_) => None,
+        }
+    }
+    pub fn udp_port(&self) -> Option<Port> {
+        match self {
+            ConnectionType::Udp(port) | ConnectionType::Both { udp: port, .. } => Some(*port),
+            ConnectionType::Tcp(_) => None,
+        }
+    }
+}
+
+/// A plain port is a TCP endpoint.
+impl From<Port> for ConnectionType {
+    fn from(port: Port) -> Self {
+        ConnectionType::Tcp(port)
+    }
+}
@@ -1,6 +1,6 @@
 use cxx::*;
 
-use crate::{primitives::{InstanceId, MajorVersion, MethodId, MinorVersion, ServiceId}, util::AsPinMut, Message, State};
+use crate::{primitives::{EventGroupId, EventId, EventType, InstanceId, MajorVersion, MethodId, MinorVersion, ServiceId}, util::AsPinMut, Message, Payload, State};
 
 /// This class contains the public API of the vsomeip implementation.
 ///
@@ -110,6 +110,57 @@ impl Application {
         unsafe { vsomeip_sys::application::stop_offer_service(self.pin_mut(), service_id, instance_id, major_version, minor_version) }
     }
 
+    /// Offers a SOME/IP event or field.
+    ///
+    /// A user application must call this method to offer an event or field
+    /// that belongs to a service instance it offers. Subscribers receive
+    /// notifications for the event once they have subscribed to one of the
+    /// event groups the event belongs to.
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the interface containing the
+    ///   event.
+    /// - `instance_id`: Instance identifier of the interface containing the
+    ///   event.
+    /// - `event_id`: Event identifier of the offered event.
+    /// - `eventgroups`: List of event groups that contain the event.
+    /// - `event_type`: Type of the event, a field caches its last value and
+    ///   sends it to every new subscriber.
+    pub fn offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType) {
+        unsafe { vsomeip_sys::application_offer_event(self.pin_mut(), service_id, instance_id, event_id, eventgroups, event_type.into()) }
+    }
+
+    /// Stops offering a SOME/IP event or field.
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the interface that contains the
+    ///   event.
+    /// - `instance_id`: Instance identifier of the interface that contains the
+    ///   event.
+    /// - `event_id`: Event identifier of the offered event.
+    pub fn stop_offer_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId) {
+        unsafe { vsomeip_sys::application::stop_offer_event(self.pin_mut(), service_id, instance_id, event_id) }
+    }
+
+    /// Sends a notification for an offered event or field to all subscribers.
+    ///
+    /// Fields are only updated (and notifications are only sent) if the
+    /// payload changed, unless `force` is set.
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the interface that contains the
+    ///   event.
+    /// - `instance_id`: Instance identifier of the interface that contains the
+    ///   event.
+    /// - `event_id`: Event identifier of the event.
+    /// - `payload`: Serialized payload of the event.
+    /// - `force`: Send the notification even if the value did not change.
+    pub fn notify(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, payload: &Payload, force: bool) {
+        let application = self.inner.as_ref().unwrap();
+
+        unsafe { vsomeip_sys::application_notify(application, service_id, instance_id, event_id, &payload.inner, force) }
+    }
+
     /// Registers the application as a client of a service instance.
     ///
     /// A user application must call this method for each service instance it
@@ -126,6 +177,72 @@ impl Application {
         unsafe { vsomeip_sys::application::request_service(self.pin_mut(), service_id, instance_id, major_version, minor_version) }
     }
 
+    /// Registers the application as user of an event or field.
+    ///
+    /// A user application must call this method before being able to receive
+    /// event or field data. Notifications are only delivered after the
+    /// application subscribed to an event group containing the event.
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the interface that contains the
+    ///   event.
+    /// - `instance_id`: Instance identifier of the interface that contains the
+    ///   event.
+    /// - `event_id`: Event identifier of the event.
+    /// - `eventgroups`: List of event groups that contain the event.
+    /// - `event_type`: Type of the event.
+    pub fn request_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId, eventgroups: &[EventGroupId], event_type: EventType) {
+        unsafe { vsomeip_sys::application_request_event(self.pin_mut(), service_id, instance_id, event_id, eventgroups, event_type.into()) }
+    }
+
+    /// Unregisters the application as user of an event or field.
+    ///
+    /// Unregistering implicitly unsubscribes from all event groups that
+    /// contain the event.
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the interface that contains the
+    ///   event.
+    /// - `instance_id`: Instance identifier of the interface that contains the
+    ///   event.
+    /// - `event_id`: Event identifier of the event.
+    pub fn release_event(&self, service_id: ServiceId, instance_id: InstanceId, event_id: EventId) {
+        unsafe { vsomeip_sys::application::release_event(self.pin_mut(), service_id, instance_id, event_id) }
+    }
+
+    /// Subscribes to an event group.
+    ///
+    /// A user application must call this method to subscribe to an event group.
+    /// Before calling subscribe it must register all events it is interested in
+    /// using [`request_event`](Self::request_event).
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the service that contains the
+    ///   event group.
+    /// - `instance_id`: Instance identifier of the service that contains the
+    ///   event group.
+    /// - `eventgroup_id`: Event group identifier of the event group.
+    /// - `major_version`: Major version number of the service.
+    /// - `event_id`: Event identifier of the event the application wants to
+    ///   receive, can be set to `ANY_EVENT`.
+    pub fn subscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, major_version: MajorVersion, event_id: EventId) {
+        unsafe { vsomeip_sys::application::subscribe(self.pin_mut(), service_id, instance_id, eventgroup_id, major_version, event_id) }
+    }
+
+    /// Unsubscribes from an event of an event group.
+    ///
+    /// # Parameters
+    /// - `service_id`: Service identifier of the service that contains the
+    ///   event group.
+    /// - `instance_id`: Instance identifier of the service that contains the
+    ///   event group.
+    /// - `eventgroup_id`: Event group identifier of the event group.
+    /// - `event_id`: Event identifier of the event, can be set to `ANY_EVENT`
+    ///   to unsubscribe from the whole event group.
+    pub fn unsubscribe(&self, service_id: ServiceId, instance_id: InstanceId, eventgroup_id: EventGroupId, event_id: EventId) {
+        unsafe { vsomeip_sys::application_unsubscribe(self.pin_mut(), service_id, instance_id, eventgroup_id, event_id) }
+    }
+
     /// Unregisters the application as a client of a service instance.
     ///
     /// A user application should call this method if it no longer needs to
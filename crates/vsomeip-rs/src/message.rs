@@ -139,7 +139,7 @@ impl Message {
     /// Get the return code from the message header.
     pub fn get_return_code(&self) -> ReturnCode {
         let message_base = unsafe { vsomeip_sys::as_message_base(&self.inner) };
-        let return_code = unsafe { vsomeip_sys::message_base::get_return_code(&message_base) };
+        let return_code = unsafe { vsomeip_sys::message_base_get_return_code(&message_base) };
         return_code.into()
     }
 
@@ -147,7 +147,7 @@ impl Message {
     pub fn set_return_code(&mut self, return_code: ReturnCode) {
         let message_base = unsafe { vsomeip_sys::as_message_base(&self.inner) };
         let pin_mut = unsafe { util::shared_to_pin(&message_base) };
-        unsafe { vsomeip_sys::message_base::set_return_code(pin_mut, return_code.into()) };
+        unsafe { vsomeip_sys::message_base_set_return_code(pin_mut, return_code.into()) };
     }
 
     /// Get the interface version from the message header.
@@ -259,6 +259,9 @@ mod tests {
 
         message.set_return_code(ReturnCode::Ok);
         assert_eq!(message.get_return_code(), ReturnCode::Ok);
+
+        message.set_return_code(ReturnCode::Application(0x20));
+        assert_eq!(message.get_return_code(), ReturnCode::Application(0x20));
     }
 
     #[test]
@@ -56,43 +56,49 @@ pub enum ReturnCode {
     WrongInterfaceVersion,
     MalformedMessage,
     WrongMessageType,
+    /// Service or method specific error, the code is in the range
+    /// `0x20..=0x5E` reserved for applications.
+    Application(u8),
     Unknown,
 }
 
-impl From<vsomeip_sys::return_code_e> for ReturnCode {
-    fn from(value: vsomeip_sys::return_code_e) -> Self {
+/// Return codes are passed to vsomeip as raw bytes, `return_code_e` only knows the predefined ones.
+impl From<u8> for ReturnCode {
+    fn from(value: u8) -> Self {
         match value {
-            vsomeip_sys::return_code_e::E_OK => Self::Ok,
-            vsomeip_sys::return_code_e::E_NOT_OK => Self::NotOk,
-            vsomeip_sys::return_code_e::E_UNKNOWN_SERVICE => Self::UnknownService,
-            vsomeip_sys::return_code_e::E_UNKNOWN_METHOD => Self::UnknownMethod,
-            vsomeip_sys::return_code_e::E_NOT_READY => Self::NotReady,
-            vsomeip_sys::return_code_e::E_NOT_REACHABLE => Self::NotReachable,
-            vsomeip_sys::return_code_e::E_TIMEOUT => Self::Timeout,
-            vsomeip_sys::return_code_e::E_WRONG_PROTOCOL_VERSION => Self::WrongProtocolVersion,
-            vsomeip_sys::return_code_e::E_WRONG_INTERFACE_VERSION => Self::WrongInterfaceVersion,
-            vsomeip_sys::return_code_e::E_MALFORMED_MESSAGE => Self::MalformedMessage,
-            vsomeip_sys::return_code_e::E_WRONG_MESSAGE_TYPE => Self::WrongMessageType,
-            vsomeip_sys::return_code_e::E_UNKNOWN => Self::Unknown,
+            0x00 => Self::Ok,
+            0x01 => Self::NotOk,
+            0x02 => Self::UnknownService,
+            0x03 => Self::UnknownMethod,
+            0x04 => Self::NotReady,
+            0x05 => Self::NotReachable,
+            0x06 => Self::Timeout,
+            0x07 => Self::WrongProtocolVersion,
+            0x08 => Self::WrongInterfaceVersion,
+            0x09 => Self::MalformedMessage,
+            0x0A => Self::WrongMessageType,
+            0x20..=0x5E => Self::Application(value),
+            _ => Self::Unknown,
         }
     }
 }
 
-impl From<ReturnCode> for vsomeip_sys::return_code_e {
+impl From<ReturnCode> for u8 {
     fn from(value: ReturnCode) -> Self {
         match value {
-            ReturnCode::Ok => vsomeip_sys::return_code_e::E_OK,
-            ReturnCode::NotOk => vsomeip_sys::return_code_e::E_NOT_OK,
-            ReturnCode::UnknownService => vsomeip_sys::return_code_e::E_UNKNOWN_SERVICE,
-            ReturnCode::UnknownMethod => vsomeip_sys::return_code_e::E_UNKNOWN_METHOD,
-            ReturnCode::NotReady => vsomeip_sys::return_code_e::E_NOT_READY,
-            ReturnCode::NotReachable => vsomeip_sys::return_code_e::E_NOT_REACHABLE,
-            ReturnCode::Timeout => vsomeip_sys::return_code_e::E_TIMEOUT,
-            ReturnCode::WrongProtocolVersion => vsomeip_sys::return_code_e::E_WRONG_PROTOCOL_VERSION,
-            ReturnCode::WrongInterfaceVersion => vsomeip_sys::return_code_e::E_WRONG_INTERFACE_VERSION,
-            ReturnCode::MalformedMessage => vsomeip_sys::return_code_e::E_MALFORMED_MESSAGE,
-            ReturnCode::WrongMessageType => vsomeip_sys::return_code_e::E_WRONG_MESSAGE_TYPE,
-            ReturnCode::Unknown => vsomeip_sys::return_code_e::E_UNKNOWN,
+            ReturnCode::Ok => 0x00,
+            ReturnCode::NotOk => 0x01,
+            ReturnCode::UnknownService => 0x02,
+            ReturnCode::UnknownMethod => 0x03,
+            ReturnCode::NotReady => 0x04,
+            ReturnCode::NotReachable => 0x05,
+            ReturnCode::Timeout => 0x06,
+            ReturnCode::WrongProtocolVersion => 0x07,
+            ReturnCode::WrongInterfaceVersion => 0x08,
+            ReturnCode::MalformedMessage => 0x09,
+            ReturnCode::WrongMessageType => 0x0A,
+            ReturnCode::Application(code) => code,
+            ReturnCode::Unknown => 0xFF,
         }
     }
 }
@@ -148,4 +154,25 @@ impl From<MessageType> for vsomeip_sys::message_type_e {
             MessageType::Unknown => vsomeip_sys::message_type_e::MT_UNKNOWN,
         }
     }
+}
+
+/// The type of an event offered or requested by an application.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventType {
+    #[default]
+    Event,
+    SelectiveEvent,
+    Field,
+}
+
+impl From<EventType> for u8 {
+    fn from(value: EventType) -> Self {
+        // Mirrors the values of `vsomeip_v3::event_type_e`
+        match value {
+            EventType::Event => 0x00,
+            EventType::SelectiveEvent => 0x01,
+            EventType::Field => 0x02,
+        }
+    }
 }
\ No newline at end of file
@@ -30,6 +30,8 @@ mod ffi2 {
         #[namespace = "vsomeip_v3"]
         type message_base = crate::ffi::vsomeip_v3::message_base;
         unsafe fn as_message_base(message: &SharedPtr<message>) -> SharedPtr<message_base>;
+        unsafe fn message_base_get_return_code(message: &SharedPtr<message_base>) -> u8;
+        unsafe fn message_base_set_return_code(message: Pin<&mut message_base>, _code: u8);
     }
     extern "C++" {
         include!("shim.hpp");
@@ -44,6 +46,16 @@ mod ffi2 {
         unsafe fn application_register_state_handler(application: Pin<&mut application>, _handler: state_handler_callback_t, user_data: *mut c_void);
         unsafe fn application_register_availability_handler(application: Pin<&mut application>, _service: u16, _instance: u16, _handler: availability_handler_callback_t, _major: u8, _minor: u32, user_data: *mut c_void);
     }
+    extern "C++" {
+        include!("shim.hpp");
+        #[namespace = "vsomeip_v3"]
+        type payload = crate::ffi::vsomeip_v3::payload;
+
+        unsafe fn application_offer_event(application: Pin<&mut application>, _service: u16, _instance: u16, _event: u16, _eventgroups: &[u16], _event_type: u8);
+        unsafe fn application_notify(application: &application, _service: u16, _instance: u16, _event: u16, _payload: &SharedPtr<payload>, _force: bool);
+        unsafe fn application_request_event(application: Pin<&mut application>, _service: u16, _instance: u16, _event: u16, _eventgroups: &[u16], _event_type: u8);
+        unsafe fn application_unsubscribe(application: Pin<&mut application>, _service: u16, _instance: u16, _eventgroup: u16, _event: u16);
+    }
 }
 
 pub use ffi::vsomeip_v3::*;
@@ -52,4 +64,10 @@ pub use callback::*;
 pub use ffi2::application_register_message_handler;
 pub use ffi2::application_register_state_handler;
 pub use ffi2::application_register_availability_handler;
-pub use ffi2::as_message_base;
\ No newline at end of file
+pub use ffi2::as_message_base;
+pub use ffi2::message_base_get_return_code;
+pub use ffi2::message_base_set_return_code;
+pub use ffi2::application_offer_event;
+pub use ffi2::application_notify;
+pub use ffi2::application_request_event;
+pub use ffi2::application_unsubscribe;
\ No newline at end of file
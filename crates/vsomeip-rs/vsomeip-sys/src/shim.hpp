@@ -2,10 +2,20 @@
 #include <vsomeip/application.hpp>
 #include <vsomeip/handler.hpp>
 #include <vsomeip/message.hpp>
+#include <set>
+#include "rust/cxx.h"
 
 std::shared_ptr<vsomeip_v3::message_base> as_message_base(const std::shared_ptr<vsomeip_v3::message>& message) {
     return std::static_pointer_cast<vsomeip_v3::message_base>(message);
 }
+// The return code is passed as a raw byte, `return_code_e` only names the predefined codes
+uint8_t message_base_get_return_code(const std::shared_ptr<vsomeip_v3::message_base>& message) {
+    return static_cast<uint8_t>(message->get_return_code());
+}
+
+void message_base_set_return_code(vsomeip_v3::message_base& message, uint8_t _code) {
+    message.set_return_code(static_cast<vsomeip_v3::return_code_e>(_code));
+}
 using c_void = void;
 typedef void (*message_handler_callback_t)(const std::shared_ptr<vsomeip_v3::message>*, c_void*);
 
@@ -32,3 +42,20 @@ void application_register_availability_handler(vsomeip_v3::application& applicat
     _minor);
 }
 
+void application_offer_event(vsomeip_v3::application& application, vsomeip_v3::service_t _service, vsomeip_v3::instance_t _instance, vsomeip_v3::event_t _event, rust::Slice<const uint16_t> _eventgroups, uint8_t _event_type) {
+    std::set<vsomeip_v3::eventgroup_t> eventgroups(_eventgroups.begin(), _eventgroups.end());
+    application.offer_event(_service, _instance, _event, eventgroups, static_cast<vsomeip_v3::event_type_e>(_event_type));
+}
+
+void application_notify(const vsomeip_v3::application& application, vsomeip_v3::service_t _service, vsomeip_v3::instance_t _instance, vsomeip_v3::event_t _event, const std::shared_ptr<vsomeip_v3::payload>& _payload, bool _force) {
+    application.notify(_service, _instance, _event, _payload, _force);
+}
+
+void application_request_event(vsomeip_v3::application& application, vsomeip_v3::service_t _service, vsomeip_v3::instance_t _instance, vsomeip_v3::event_t _event, rust::Slice<const uint16_t> _eventgroups, uint8_t _event_type) {
+    std::set<vsomeip_v3::eventgroup_t> eventgroups(_eventgroups.begin(), _eventgroups.end());
+    application.request_event(_service, _instance, _event, eventgroups, static_cast<vsomeip_v3::event_type_e>(_event_type));
+}
+
+void application_unsubscribe(vsomeip_v3::application& application, vsomeip_v3::service_t _service, vsomeip_v3::instance_t _instance, vsomeip_v3::eventgroup_t _eventgroup, vsomeip_v3::event_t _event) {
+    application.unsubscribe(_service, _instance, _eventgroup, _event);
+}
@@ -1,9 +1,14 @@
 
-use std::{io::Write, net::IpAddr};
+use std::{io::{self, Write}, net::IpAddr, path::{Path, PathBuf}, sync::Mutex, time::Duration};
 
-use serde_json::json;
-use vsomeip_rs::{InstanceId, MajorVersion, MinorVersion, ServiceId};
-use tempfile::NamedTempFile;
+use serde_json::{json, Value};
+use someip_types::{InstanceId, MajorVersion, MinorVersion, ServiceId};
+use tempfile::{NamedTempFile, TempPath};
+
+mod logging;
+
+pub use logging::*;
+pub use someip_types::ConnectionType;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VsomeIpConfig {
@@ -12,9 +17,20 @@ pub struct VsomeIpConfig {
     pub addr: Option<IpAddr>,
     pub netmask: Option<IpAddr>,
     pub addr_mode: AddressingMode,
-    pub service_discovery: bool,   
+    pub service_discovery: ServiceDiscoveryConfig,
+    pub logging: LoggingConfig,
     pub instance_id: InstanceId,
     pub routing: Option<String>,
+    /// Configuration this one was loaded from, the generated configuration is merged into it.
+    pub base: Option<Value>,
+    /// File the `base` configuration was read from.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for VsomeIpConfig {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VsomeIpConfig {
@@ -22,12 +38,15 @@ impl VsomeIpConfig {
         Self {
             app_id: ("smip_app".to_string(), 0),
             services: vec![],
-            service_discovery: false,
+            service_discovery: ServiceDiscoveryConfig::default(),
+            logging: LoggingConfig::default(),
             addr_mode: AddressingMode::Unicast,
             netmask: None,
             addr: None,
             instance_id: 0,
-            routing: None
+            routing: None,
+            base: None,
+            path: None,
         }
     }
 
@@ -37,7 +56,17 @@ impl VsomeIpConfig {
     }
 
     pub fn service_discovery(mut self, enable: bool) -> Self {
-        self.service_discovery = enable;
+        self.service_discovery.enable = enable;
+        self
+    }
+
+    pub fn service_discovery_config(mut self, config: ServiceDiscoveryConfig) -> Self {
+        self.service_discovery = config;
+        self
+    }
+
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.logging = logging;
         self
     }
     
@@ -61,6 +90,66 @@ impl VsomeIpConfig {
         self
     }
 
+    /// Loads an existing vsomeip configuration file.
+    ///
+    /// The settings `VsomeIpConfig` knows about can be changed afterwards, everything
+    /// else in the file is kept as it is when the configuration is built.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut config = Self::from_json(&std::fs::read_to_string(path)?)?;
+        config.path = Some(path.to_owned());
+
+        Ok(config)
+    }
+
+    /// Loads the configuration file `VSOMEIP_CONFIGURATION` points to, if it's set.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        match std::env::var_os("VSOMEIP_CONFIGURATION") {
+            Some(path) => Self::from_file(path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a vsomeip configuration, see [`VsomeIpConfig::from_file`].
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let base: Value = serde_json::from_str(json)?;
+        let mut config = Self::new();
+
+        config.addr = parse_field(&base, "unicast", parse_addr)?;
+        config.netmask = parse_field(&base, "netmask", parse_addr)?;
+        // Newer vsomeip versions also accept an object, which is left to the base configuration
+        config.routing = base.get("routing").and_then(Value::as_str).map(str::to_owned);
+
+        if let Some(application) = base.get("applications").and_then(|applications| applications.get(0)) {
+            let name = parse_field(application, "name", |value| value.as_str().map(str::to_owned))?;
+            let id = parse_field(application, "id", parse_number)?;
+
+            if let (Some(name), Some(id)) = (name, id) {
+                config.app_id = (name, id);
+            }
+        }
+
+        if let Some(services) = base.get("services").and_then(Value::as_array) {
+            for service in services {
+                if let Some(service) = VSomeIpServiceConfig::from_json(service)? {
+                    config.services.push(service);
+                }
+            }
+        }
+
+        if let Some(service_discovery) = base.get("service-discovery") {
+            config.service_discovery = ServiceDiscoveryConfig::from_json(service_discovery)?;
+        }
+
+        if let Some(logging) = base.get("logging") {
+            config.logging = LoggingConfig::from_json(logging)?;
+        }
+
+        config.base = Some(base);
+
+        Ok(config)
+    }
+
     fn build_addr_mode(&self) -> String {
         match self.addr_mode {
             AddressingMode::Unicast => "unicast".into(),
@@ -93,50 +182,181 @@ impl VsomeIpConfig {
         let mut json = json!({
             addr_mode: addr,
             "netmask": netmask,
-            "logging": {
-                "level": "debug",
-                "console": true,
-                "file": {
-                    "enable": false,
-                    "path": "/tmp/vsomeip.log",
-                },
-                "dlt": false,
-            },
+            "logging": self.logging.build(),
             "applications": [{
                 "name": self.app_id.0,
                 "id": self.app_id.1.to_string(),
             }],
             "services": self.services.iter().map(move |service| {
-                json!({
+                let mut json = json!({
                     "service": service.id.to_string(),
-                    "instance": self.instance_id.to_string(),
-                    "reliable": {
-                        "port": match service.conn_type {
-                            ConnectionType::Tcp(port) => port,
-                            ConnectionType::Udp(port) => port,
-                        },
-                        "enable-magic-cookie": false,
-                    },
+                    "instance": service.instance_id.unwrap_or(self.instance_id).to_string(),
                     addr_mode: self.addr,
-                })
+                });
+
+                if let Some(port) = service.conn_type.tcp_port() {
+                    json["reliable"] = json!({
+                        "port": port,
+                        "enable-magic-cookie": false,
+                    });
+                }
+                if let Some(port) = service.conn_type.udp_port() {
+                    json["unreliable"] = json!(port);
+                }
+
+                json
             }).collect::<Vec<_>>(),
-            "service-discovery": {
-                "enable": self.service_discovery,
-            },
+            "service-discovery": self.service_discovery.build(),
         });
 
         if let Some(routing) = self.routing {
             json["routing"] = json!(routing);
         }
 
-        println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        if let Some(mut base) = self.base {
+            merge(&mut base, json);
+            json = base;
+        }
+
+        log::debug!("Generated vsomeip configuration: {}", serde_json::to_string_pretty(&json).unwrap());
         json.to_string()
     } 
 }
+/// Transport used for service discovery messages.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum ConnectionType {
-    Tcp(u16),
-    Udp(u16)
+pub enum SdProtocol {
+    Udp,
+    Tcp,
+}
+
+/// Settings of the `service-discovery` section, vsomeip's defaults apply to everything that isn't set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServiceDiscoveryConfig {
+    pub enable: bool,
+    /// Multicast address service discovery messages are sent to.
+    pub multicast: Option<IpAddr>,
+    pub port: Option<u16>,
+    pub protocol: Option<SdProtocol>,
+    /// Range of the random delay before the first offer is sent.
+    pub initial_delay_min: Option<Duration>,
+    pub initial_delay_max: Option<Duration>,
+    /// Delay before the first repetition of an offer, doubled for every following one.
+    pub repetitions_base_delay: Option<Duration>,
+    pub repetitions_max: Option<u8>,
+    /// Interval of the offers sent after the repetition phase.
+    pub cyclic_offer_delay: Option<Duration>,
+    /// Lifetime of offers and subscriptions in seconds.
+    pub ttl: Option<u32>,
+}
+
+impl ServiceDiscoveryConfig {
+    /// An enabled service discovery with vsomeip's default settings.
+    pub fn new() -> Self {
+        Self {
+            enable: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn enable(mut self, enable: bool) -> Self {
+        self.enable = enable;
+        self
+    }
+
+    pub fn multicast(mut self, multicast: IpAddr, port: u16) -> Self {
+        self.multicast = Some(multicast);
+        self.port = Some(port);
+        self
+    }
+
+    pub fn protocol(mut self, protocol: SdProtocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    pub fn initial_delay(mut self, min: Duration, max: Duration) -> Self {
+        self.initial_delay_min = Some(min);
+        self.initial_delay_max = Some(max);
+        self
+    }
+
+    pub fn repetitions(mut self, base_delay: Duration, max: u8) -> Self {
+        self.repetitions_base_delay = Some(base_delay);
+        self.repetitions_max = Some(max);
+        self
+    }
+
+    pub fn cyclic_offer_delay(mut self, delay: Duration) -> Self {
+        self.cyclic_offer_delay = Some(delay);
+        self
+    }
+
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    fn from_json(json: &Value) -> io::Result<Self> {
+        let millis = |value: &Value| parse_number(value).map(Duration::from_millis);
+
+        Ok(Self {
+            enable: parse_field(json, "enable", parse_bool)?.unwrap_or(true),
+            multicast: parse_field(json, "multicast", parse_addr)?,
+            port: parse_field(json, "port", parse_number)?,
+            protocol: parse_field(json, "protocol", |value| match value.as_str()? {
+                "udp" => Some(SdProtocol::Udp),
+                "tcp" => Some(SdProtocol::Tcp),
+                _ => None,
+            })?,
+            initial_delay_min: parse_field(json, "initial_delay_min", millis)?,
+            initial_delay_max: parse_field(json, "initial_delay_max", millis)?,
+            repetitions_base_delay: parse_field(json, "repetitions_base_delay", millis)?,
+            repetitions_max: parse_field(json, "repetitions_max", parse_number)?,
+            cyclic_offer_delay: parse_field(json, "cyclic_offer_delay", millis)?,
+            ttl: parse_field(json, "ttl", parse_number)?,
+        })
+    }
+
+    fn build(&self) -> serde_json::Value {
+        let mut json = json!({
+            "enable": self.enable,
+        });
+
+        if let Some(multicast) = self.multicast {
+            json["multicast"] = json!(multicast);
+        }
+        if let Some(port) = self.port {
+            json["port"] = json!(port);
+        }
+        if let Some(protocol) = self.protocol {
+            json["protocol"] = json!(match protocol {
+                SdProtocol::Udp => "udp",
+                SdProtocol::Tcp => "tcp",
+            });
+        }
+
+        // Delays are configured in milliseconds
+        let delays = [
+            ("initial_delay_min", self.initial_delay_min),
+            ("initial_delay_max", self.initial_delay_max),
+            ("repetitions_base_delay", self.repetitions_base_delay),
+            ("cyclic_offer_delay", self.cyclic_offer_delay),
+        ];
+        for (key, delay) in delays {
+            if let Some(delay) = delay {
+                json[key] = json!(delay.as_millis() as u64);
+            }
+        }
+
+        if let Some(repetitions_max) = self.repetitions_max {
+            json["repetitions_max"] = json!(repetitions_max);
+        }
+        if let Some(ttl) = self.ttl {
+            json["ttl"] = json!(ttl);
+        }
+
+        json
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -148,14 +368,47 @@ pub enum AddressingMode {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VSomeIpServiceConfig {
     pub id: ServiceId,
+    /// Instance of the service, the `instance_id` of the [`VsomeIpConfig`] if not set.
+    pub instance_id: Option<InstanceId>,
     pub conn_type: ConnectionType,
     pub major_version: MajorVersion,
     pub minor_version: MinorVersion
 }
+impl VSomeIpServiceConfig {
+    /// Parses an entry of the `services` section, entries without an endpoint are skipped.
+    fn from_json(json: &Value) -> io::Result<Option<Self>> {
+        let Some(id) = parse_field(json, "service", parse_number)? else {
+            return Ok(None);
+        };
+        let instance_id = parse_field(json, "instance", parse_number)?;
+
+        let tcp = match json.get("reliable") {
+            Some(reliable @ Value::Object(_)) => parse_field(reliable, "port", parse_number)?,
+            _ => parse_field(json, "reliable", parse_number)?,
+        };
+        let udp = parse_field(json, "unreliable", parse_number)?;
+
+        let conn_type = match (tcp, udp) {
+            (Some(tcp), Some(udp)) => ConnectionType::Both { tcp, udp },
+            (Some(tcp), None) => ConnectionType::Tcp(tcp),
+            (None, Some(udp)) => ConnectionType::Udp(udp),
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            id,
+            instance_id,
+            conn_type,
+            ..Default::default()
+        }))
+    }
+}
+
 impl Default for VSomeIpServiceConfig {
     fn default() -> Self {
         Self {
             id: 0,
+            instance_id: None,
             conn_type: ConnectionType::Tcp(30509),
             major_version: 0,
             minor_version: 0
@@ -164,21 +417,153 @@ impl Default for VSomeIpServiceConfig {
     }
 }
 
-pub fn set_vsomeip_config(config: &str) {
-    if std::env::var("VSOMEIP_CONFIGURATION").is_ok() {
-        println!("VSOMEIP_CONFIGURATION is already set, using that...");
-        return;
+/// Parses `key` of a JSON object, `None` if it's missing.
+fn parse_field<T>(json: &Value, key: &str, parse: impl Fn(&Value) -> Option<T>) -> io::Result<Option<T>> {
+    match json.get(key) {
+        Some(value) => parse(value)
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid `{key}` in vsomeip configuration: {value}"))),
+        None => Ok(None),
+    }
+}
+
+/// vsomeip accepts numbers as JSON numbers as well as decimal or `0x` prefixed hex strings.
+fn parse_number<T: TryFrom<u64>>(value: &Value) -> Option<T> {
+    let number = match value {
+        Value::Number(number) => number.as_u64()?,
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+            None => string.parse().ok()?,
+        },
+        _ => return None,
+    };
+
+    number.try_into().ok()
+}
+
+fn parse_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(value) => Some(*value),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_addr(value: &Value) -> Option<IpAddr> {
+    value.as_str()?.parse().ok()
+}
+
+/// Identifies the entries of the `applications` and `services` sections.
+fn entry_key(entry: &Value) -> Option<String> {
+    if let Some(name) = entry.get("name").and_then(Value::as_str) {
+        return Some(name.to_owned());
+    }
+
+    let service: u64 = parse_number(entry.get("service")?)?;
+    let instance: u64 = parse_number(entry.get("instance")?)?;
+
+    Some(format!("{service:#06x}.{instance:#06x}"))
+}
+
+/// Merges `overrides` into `base`.
+///
+/// Objects are merged key by key, entries of arrays like `services` are merged into the
+/// entry of `base` they share a name or service and instance id with. Everything else in
+/// `overrides` replaces the value in `base`.
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (Value::Array(base), Value::Array(overrides)) if overrides.iter().all(|entry| entry_key(entry).is_some()) => {
+            for entry in overrides {
+                let key = entry_key(&entry);
+
+                match base.iter_mut().find(|base| entry_key(base) == key) {
+                    Some(base) => merge(base, entry),
+                    None => base.push(entry),
+                }
+            }
+        },
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Path of the last configuration written by [`set_vsomeip_config`].
+static GENERATED_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// A configuration file written by [`set_vsomeip_config`], deleted once dropped.
+pub struct ConfigFile {
+    path: TempPath,
+    /// Pipe vsomeip's log is forwarded from.
+    _log_pipe: Option<logging::LogPipe>,
+}
+
+impl ConfigFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Writes the configuration to a temporary file and points `VSOMEIP_CONFIGURATION` to it.
+///
+/// A `VSOMEIP_CONFIGURATION` set outside of the process takes precedence, unless `config`
+/// was loaded from that file with [`VsomeIpConfig::from_env`] or [`VsomeIpConfig::from_file`].
+/// The returned file has to be kept for as long as the application runs.
+pub fn set_vsomeip_config(config: &VsomeIpConfig) -> Option<ConfigFile> {
+    let mut generated_path = GENERATED_PATH.lock().unwrap();
+
+    if let Some(path) = std::env::var_os("VSOMEIP_CONFIGURATION").map(PathBuf::from) {
+        let generated = generated_path.as_ref() == Some(&path);
+        let merged = config.path.as_ref() == Some(&path);
+
+        if !generated && !merged {
+            log::warn!(
+                "VSOMEIP_CONFIGURATION is set to {}, vsomeip uses it instead of the configuration generated for {}. Load it with `VsomeIpConfig::from_env` to merge both.",
+                path.display(),
+                config.app_id.0,
+            );
+            return None;
+        }
+    }
+
+    let mut config = config.clone();
+    let mut log_pipe = None;
+
+    if config.logging.forward {
+        match logging::forward_log(&config.app_id.0) {
+            Ok((pipe, path)) => {
+                config.logging.file = Some(path);
+                log_pipe = Some(pipe);
+            },
+            Err(err) => log::warn!("Failed to forward the vsomeip log: {}", err),
+        }
     }
 
+    let config = config.build();
+
     let mut temp_file = NamedTempFile::new().expect("Failed to create temporary file");
-    temp_file.write(config.as_ref()).expect("Failed to write to temporary file");
+    temp_file.write_all(config.as_ref()).expect("Failed to write to temporary file");
     temp_file.flush().expect("Failed to flush temporary file");
 
-    let config_path = temp_file.path();
+    let config_path = temp_file.into_temp_path();
 
-    println!("Wrote vsomeip config to {}", config_path.display());
+    log::info!("Wrote vsomeip config to {}", config_path.display());
 
-    std::env::set_var("VSOMEIP_CONFIGURATION", config_path);
+    std::env::set_var("VSOMEIP_CONFIGURATION", &config_path);
+    *generated_path = Some(config_path.to_path_buf());
+
+    Some(ConfigFile {
+        path: config_path,
+        _log_pipe: log_pipe,
+    })
 }
 
 #[cfg(test)]
@@ -198,12 +583,15 @@ mod tests {
                     ..Default::default()
                 }
             ],
-            service_discovery: false,
+            service_discovery: ServiceDiscoveryConfig::default(),
+            logging: LoggingConfig::default(),
             addr_mode: AddressingMode::Unicast,
             netmask: Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
             addr: Some(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 23))),
             instance_id: 3,
-            routing: None
+            routing: None,
+            base: None,
+            path: None,
         };
 
         let actual = config.build();
@@ -252,12 +640,15 @@ mod tests {
                     ..Default::default()
                 }
             ],
-            service_discovery: true,
+            service_discovery: ServiceDiscoveryConfig::new(),
+            logging: LoggingConfig::default(),
             addr_mode: AddressingMode::Unicast,
             netmask: Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
             addr: Some(IpAddr::V4(Ipv4Addr::new(192, 168,0 ,23))),
             instance_id: 3,
-            routing: None
+            routing: None,
+            base: None,
+            path: None,
         };
 
         let actual = config.build();
@@ -304,4 +695,204 @@ mod tests {
         assert_eq!(actual_json, expected_json);
 
     }
+
+    #[test]
+    fn test_config_with_multiple_instances() {
+        let config = VsomeIpConfig::new()
+            .application_id(("smip_app".to_string(), 1))
+            .instance_id(3)
+            .addr(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 23)))
+            .netmask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)))
+            .service(VSomeIpServiceConfig {
+                id: 2,
+                instance_id: Some(1),
+                conn_type: ConnectionType::Tcp(30509),
+                ..Default::default()
+            })
+            .service(VSomeIpServiceConfig {
+                id: 2,
+                instance_id: Some(2),
+                conn_type: ConnectionType::Tcp(30510),
+                ..Default::default()
+            })
+            .service(VSomeIpServiceConfig {
+                id: 4,
+                conn_type: ConnectionType::Tcp(30511),
+                ..Default::default()
+            });
+
+        let actual_json: serde_json::Value = serde_json::from_str(&config.build()).unwrap();
+
+        let expected_json = serde_json::json!([
+            {
+                "service": "2",
+                "instance": "1",
+                "reliable": { "port": 30509, "enable-magic-cookie": false },
+                "unicast": "192.168.0.23"
+            },
+            {
+                "service": "2",
+                "instance": "2",
+                "reliable": { "port": 30510, "enable-magic-cookie": false },
+                "unicast": "192.168.0.23"
+            },
+            {
+                "service": "4",
+                "instance": "3",
+                "reliable": { "port": 30511, "enable-magic-cookie": false },
+                "unicast": "192.168.0.23"
+            }
+        ]);
+
+        assert_eq!(actual_json["services"], expected_json);
+    }
+
+    const FILE_CONFIG: &str = r#"{
+        "unicast": "10.0.0.2",
+        "netmask": "255.255.255.0",
+        "logging": { "level": "info", "console": true },
+        "applications": [
+            { "name": "camera", "id": "0x1212" },
+            { "name": "recorder", "id": "0x1313" }
+        ],
+        "services": [
+            {
+                "service": "0x1234",
+                "instance": "0x0001",
+                "reliable": { "port": "30509", "enable-magic-cookie": "false" },
+                "events": [ { "event": "0x8001", "is_field": "false" } ]
+            },
+            { "service": "0x4321", "instance": "0x0001", "unreliable": "30510" }
+        ],
+        "routing": "camera",
+        "service-discovery": { "enable": "true", "multicast": "224.0.0.1", "port": "30490", "ttl": "3" },
+        "tracing": { "enable": "false" }
+    }"#;
+
+    #[test]
+    fn test_config_from_json() {
+        let config = VsomeIpConfig::from_json(FILE_CONFIG).unwrap();
+
+        assert_eq!(config.app_id, ("camera".to_string(), 0x1212));
+        assert_eq!(config.addr, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+        assert_eq!(config.netmask, Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))));
+        assert_eq!(config.routing.as_deref(), Some("camera"));
+        assert_eq!(config.services, vec![
+            VSomeIpServiceConfig {
+                id: 0x1234,
+                instance_id: Some(1),
+                conn_type: ConnectionType::Tcp(30509),
+                ..Default::default()
+            },
+            VSomeIpServiceConfig {
+                id: 0x4321,
+                instance_id: Some(1),
+                conn_type: ConnectionType::Udp(30510),
+                ..Default::default()
+            },
+        ]);
+        assert_eq!(config.service_discovery, ServiceDiscoveryConfig::new()
+            .multicast(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 1)), 30490)
+            .ttl(3));
+
+        assert!(VsomeIpConfig::from_json(r#"{ "unicast": "not an address" }"#).is_err());
+    }
+
+    #[test]
+    fn test_merge_config_into_file() {
+        let mut config = VsomeIpConfig::from_json(FILE_CONFIG).unwrap()
+            .service(VSomeIpServiceConfig {
+                id: 0x1234,
+                instance_id: Some(2),
+                conn_type: ConnectionType::Tcp(30511),
+                ..Default::default()
+            });
+        config.services[0].conn_type = ConnectionType::Tcp(30600);
+
+        let actual_json: serde_json::Value = serde_json::from_str(&config.build()).unwrap();
+
+        assert_eq!(actual_json["logging"]["level"], "info");
+        assert_eq!(actual_json["tracing"], serde_json::json!({ "enable": "false" }));
+        assert_eq!(actual_json["applications"].as_array().unwrap().len(), 2);
+
+        let services = actual_json["services"].as_array().unwrap();
+        assert_eq!(services.len(), 3);
+        assert_eq!(services[0]["reliable"]["port"], 30600);
+        assert_eq!(services[0]["events"], serde_json::json!([ { "event": "0x8001", "is_field": "false" } ]));
+        assert_eq!(services[1]["unreliable"], 30510);
+        assert_eq!(services[2]["instance"], "2");
+
+        assert_eq!(actual_json["service-discovery"]["multicast"], "224.0.0.1");
+        assert_eq!(actual_json["service-discovery"]["ttl"], 3);
+    }
+
+    #[test]
+    fn test_service_discovery_settings() {
+        let config = VsomeIpConfig::new()
+            .addr(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .netmask(IpAddr::V4(Ipv4Addr::new(255, 0, 0, 0)))
+            .service_discovery_config(ServiceDiscoveryConfig::new()
+                .multicast(IpAddr::V4(Ipv4Addr::new(224, 244, 224, 245)), 30490)
+                .protocol(SdProtocol::Udp)
+                .initial_delay(Duration::from_millis(10), Duration::from_millis(100))
+                .repetitions(Duration::from_millis(200), 3)
+                .cyclic_offer_delay(Duration::from_secs(2))
+                .ttl(3));
+
+        let actual_json: serde_json::Value = serde_json::from_str(&config.build()).unwrap();
+
+        let expected_json = serde_json::json!({
+            "enable": true,
+            "multicast": "224.244.224.245",
+            "port": 30490,
+            "protocol": "udp",
+            "initial_delay_min": 10,
+            "initial_delay_max": 100,
+            "repetitions_base_delay": 200,
+            "repetitions_max": 3,
+            "cyclic_offer_delay": 2000,
+            "ttl": 3
+        });
+
+        assert_eq!(actual_json["service-discovery"], expected_json);
+    }
+
+    #[test]
+    fn test_config_with_unreliable_endpoints() {
+        let config = VsomeIpConfig::new()
+            .application_id(("smip_app".to_string(), 1))
+            .instance_id(3)
+            .addr(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 23)))
+            .netmask(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)))
+            .service(VSomeIpServiceConfig {
+                id: 2,
+                conn_type: ConnectionType::Udp(30509),
+                ..Default::default()
+            })
+            .service(VSomeIpServiceConfig {
+                id: 4,
+                conn_type: ConnectionType::Both { tcp: 30510, udp: 30511 },
+                ..Default::default()
+            });
+
+        let actual_json: serde_json::Value = serde_json::from_str(&config.build()).unwrap();
+
+        let expected_json = serde_json::json!([
+            {
+                "service": "2",
+                "instance": "3",
+                "unreliable": 30509,
+                "unicast": "192.168.0.23"
+            },
+            {
+                "service": "4",
+                "instance": "3",
+                "reliable": { "port": 30510, "enable-magic-cookie": false },
+                "unreliable": 30511,
+                "unicast": "192.168.0.23"
+            }
+        ]);
+
+        assert_eq!(actual_json["services"], expected_json);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,283 @@
+use std::{ffi::CString, fs::OpenOptions, io::{self, Read}, os::unix::{ffi::OsStrExt, fs::OpenOptionsExt, io::AsRawFd}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::{self, JoinHandle}};
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use crate::{parse_bool, parse_field};
+
+/// Severity of the messages vsomeip logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Fatal => "fatal",
+        }
+    }
+    fn parse(level: &str) -> Option<Self> {
+        match level {
+            "trace" | "verbose" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warning" => Some(LogLevel::Warning),
+            "error" => Some(LogLevel::Error),
+            "fatal" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warning => log::Level::Warn,
+            LogLevel::Error | LogLevel::Fatal => log::Level::Error,
+        }
+    }
+}
+
+/// Settings of the `logging` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoggingConfig {
+    pub level: LogLevel,
+    /// Whether vsomeip prints its log to the console.
+    pub console: bool,
+    /// File vsomeip appends its log to.
+    pub file: Option<PathBuf>,
+    /// Whether vsomeip sends its log to DLT.
+    pub dlt: bool,
+    /// Whether vsomeip's log is forwarded to the `log` crate, with `vsomeip` as target.
+    pub forward: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Debug,
+            console: true,
+            file: None,
+            dlt: false,
+            forward: false,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn console(mut self, console: bool) -> Self {
+        self.console = console;
+        self
+    }
+
+    pub fn file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn dlt(mut self, dlt: bool) -> Self {
+        self.dlt = dlt;
+        self
+    }
+
+    /// Forwards vsomeip's log to the `log` crate instead of printing it to the console.
+    ///
+    /// vsomeip writes its log to a pipe that smip reads from, so this replaces the `file` setting.
+    pub fn forward_to_log(mut self) -> Self {
+        self.forward = true;
+        self.console = false;
+        self
+    }
+
+    pub(crate) fn from_json(json: &Value) -> io::Result<Self> {
+        let default = Self::default();
+
+        let file = match json.get("file") {
+            Some(file) if parse_field(file, "enable", parse_bool)?.unwrap_or(false) => {
+                parse_field(file, "path", |value| value.as_str().map(PathBuf::from))?
+            },
+            _ => None,
+        };
+
+        Ok(Self {
+            level: parse_field(json, "level", |value| LogLevel::parse(value.as_str()?))?.unwrap_or(default.level),
+            console: parse_field(json, "console", parse_bool)?.unwrap_or(default.console),
+            file,
+            dlt: parse_field(json, "dlt", parse_bool)?.unwrap_or(default.dlt),
+            forward: false,
+        })
+    }
+
+    pub(crate) fn build(&self) -> Value {
+        json!({
+            "level": self.level.as_str(),
+            "console": self.console,
+            "file": {
+                "enable": self.file.is_some(),
+                "path": self.file.as_deref().unwrap_or(Path::new("/tmp/vsomeip.log")),
+            },
+            "dlt": self.dlt,
+        })
+    }
+}
+
+/// How long the forwarder waits for vsomeip to write before checking whether it should stop.
+const POLL_INTERVAL_MS: libc::c_int = 100;
+
+/// Pipe vsomeip's log is forwarded from, the forwarder stops and the pipe is removed once dropped.
+pub(crate) struct LogPipe {
+    _dir: TempDir,
+    stop: Arc<AtomicBool>,
+    forwarder: Option<JoinHandle<()>>,
+}
+
+impl Drop for LogPipe {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(forwarder) = self.forwarder.take() {
+            let _ = forwarder.join();
+        }
+    }
+}
+
+/// Creates a pipe for vsomeip to write its log to and forwards the lines read from it to the `log` crate.
+pub(crate) fn forward_log(application: &str) -> io::Result<(LogPipe, PathBuf)> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("vsomeip.log");
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Opening the write end as well doesn't wait for vsomeip to open the pipe and
+    // keeps it from reporting a hang up whenever vsomeip closes it
+    let mut pipe = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(&path)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+
+    let forwarder = thread::Builder::new()
+        .name(format!("{application}-log"))
+        .spawn(move || {
+            let mut pending = Vec::new();
+            let mut buffer = [0; 4096];
+
+            while !stopped.load(Ordering::Relaxed) {
+                let mut poll_fd = libc::pollfd { fd: pipe.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+                if unsafe { libc::poll(&mut poll_fd, 1, POLL_INTERVAL_MS) } <= 0 {
+                    continue;
+                }
+
+                match pipe.read(&mut buffer) {
+                    Ok(read) => pending.extend_from_slice(&buffer[..read]),
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => return,
+                }
+
+                while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                    let line = String::from_utf8_lossy(&pending[..end]).into_owned();
+                    pending.drain(..=end);
+
+                    let (level, message) = parse_log_line(&line);
+                    log::log!(target: "vsomeip", level.into(), "{}", message);
+                }
+            }
+        })?;
+
+    Ok((LogPipe { _dir: dir, stop, forwarder: Some(forwarder) }, path))
+}
+
+/// Splits a line of vsomeip's log, like `2024-01-01 12:00:00.000000 [info] Application started`,
+/// into its level and message.
+fn parse_log_line(line: &str) -> (LogLevel, &str) {
+    let level = line.find('[').and_then(|start| {
+        let end = start + line[start..].find(']')?;
+        let level = LogLevel::parse(&line[start + 1..end])?;
+
+        Some((level, line[end + 1..].trim_start()))
+    });
+
+    level.unwrap_or((LogLevel::Info, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_line() {
+        assert_eq!(parse_log_line("2024-01-01 12:00:00.000000 [info] Application started"), (LogLevel::Info, "Application started"));
+        assert_eq!(parse_log_line("2024-01-01 12:00:00.000000 [verbose] Sending [1234.0001]"), (LogLevel::Trace, "Sending [1234.0001]"));
+        assert_eq!(parse_log_line("2024-01-01 12:00:00.000000 [warning] Routing info lost"), (LogLevel::Warning, "Routing info lost"));
+        assert_eq!(parse_log_line("no level"), (LogLevel::Info, "no level"));
+    }
+
+    #[test]
+    fn test_forward_log_stops() {
+        let (pipe, path) = forward_log("Test").unwrap();
+        std::fs::write(&path, "2024-01-01 12:00:00.000000 [info] Application started\n").unwrap();
+        thread::sleep(std::time::Duration::from_millis(50));
+
+        // Joins the forwarder, which is waiting for more lines by now
+        drop(pipe);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_forward_log_unopened() {
+        let (pipe, path) = forward_log("Test").unwrap();
+        thread::sleep(std::time::Duration::from_millis(50));
+
+        // vsomeip never opened the pipe, the forwarder must not wait for it
+        drop(pipe);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_logging_config() {
+        let config = LoggingConfig::new()
+            .level(LogLevel::Warning)
+            .console(false)
+            .file("/var/log/vsomeip.log")
+            .dlt(true);
+
+        let expected_json = json!({
+            "level": "warning",
+            "console": false,
+            "file": { "enable": true, "path": "/var/log/vsomeip.log" },
+            "dlt": true,
+        });
+
+        assert_eq!(config.build(), expected_json);
+        assert_eq!(LoggingConfig::from_json(&expected_json).unwrap(), config);
+    }
+}
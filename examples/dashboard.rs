@@ -1,6 +1,6 @@
 use std::time::Instant;
 
-use smip::{methods_impl, service};
+use smip::{methods_impl, service, Event, Field};
 use smip_core::Runtime;
 
 use noise::*;
@@ -58,7 +58,10 @@ pub enum Indicator {
 struct Dashboard {
     speed_sensor: SpeedSensor,
     battery: BatterySensor,
-    indicator: Indicator,
+    #[smip_field(getter = 0x5555, setter = 0x5556, notifier = 0x8002, eventgroup = 0x0001)]
+    indicator: Field<i32>,
+    #[smip_event(id = 0x8001, eventgroup = 0x0001)]
+    speed_changed: Event<u32>,
 }
 
 #[methods_impl]
@@ -67,7 +70,8 @@ impl Dashboard {
         Self {
             speed_sensor: SpeedSensor::new(10..150),
             battery: BatterySensor::new(100),
-            indicator: Indicator::Off
+            indicator: Field::new(Indicator::Off as i32),
+            speed_changed: Event::new(),
         }
     }
     #[smip_method(id = 0x3333)]
@@ -78,18 +82,25 @@ impl Dashboard {
     fn battery(&mut self) -> u8 {
         self.battery.value()
     }
-    #[smip_method(id = 0x5555)]
-    fn indicator(&self) -> i32 {
-        format!("{:?}", self.indicator);
-
-        self.indicator as i32
-    }
 }
 
 fn main() {
     let config = smip::RuntimeConfig::new("Dashboard", 0x1313, 0x2222);
 
-    let application = Runtime::new(config).service(Dashboard::new(), 30509);
+    let dashboard = Dashboard::new();
+
+    // Publish the speed from a separate sensor loop instead of waiting to be polled
+    let speed_changed = dashboard.speed_changed.clone();
+    std::thread::spawn(move || {
+        let mut speed_sensor = SpeedSensor::new(10..150);
+
+        loop {
+            let _ = speed_changed.notify(&speed_sensor.value());
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    });
+
+    let application = Runtime::new(config).service(dashboard, smip::ConnectionType::Both { tcp: 30509, udp: 30509 });
 
     let _ = application.run();
 }
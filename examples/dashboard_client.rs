@@ -20,10 +20,21 @@ fn main() -> Result<(), Box<dyn Error>>{
 
     let client = Client::new(&vsomeip_config)?;
 
+    let _speed_subscription = client.subscribe(0x0001, 0x8001, |speed: Result<u32, _>| {
+        match speed {
+            Ok(speed) => println!("Speed: {}", speed),
+            Err(err) => println!("Invalid speed notification: {}", err),
+        }
+    });
+
+    let _indicator_subscription = client.subscribe_field(0x0001, 0x8002, |indicator: Result<i32, _>| {
+        match indicator {
+            Ok(indicator) => println!("Indicator: {}", indicator),
+            Err(err) => println!("Invalid indicator notification: {}", err),
+        }
+    });
+
     loop {
-        let speed: i32 = client.send(0x3333, ())?;
-        println!("Speed: {}", speed);
-        
         let battery: u8 = client.send(0x4444, ())?;
         println!("Battery: {}", battery);
 
@@ -31,7 +31,7 @@ fn main() {
     let application = Runtime::new(config).service(
         MyService {
         x: 0
-    }, 30509);
+    }, smip::ConnectionType::Both { tcp: 30509, udp: 30509 });
 
     let _ = application.run();
 }
@@ -11,7 +11,8 @@ fn main() -> Result<(), Box<dyn Error>>{
             id: 0x1234,
             conn_type: smip::ConnectionType::Udp(30509),
             major_version: 1,
-            minor_version: 0
+            minor_version: 0,
+            ..Default::default()
         });
 
 
@@ -1,4 +1,5 @@
 pub use smip_proc_macros::*;
 pub use smip_core::*;
 pub use someip_types::*;
+#[cfg(feature = "vsomeip")]
 pub use vsomeip_compat::*;
\ No newline at end of file